@@ -0,0 +1,91 @@
+//! 用录制的历史行情回放策略
+//!
+//! 演示如何把实时的 `WebSocketStream` 换成一份录制好的 JSON Lines 行情文件：
+//! `backtest::replay` 按照原始事件驱动同一个 `strategy::dispatch`，这里跑的就是
+//! `polyfill_rs::snipe::SnipeStrategy` 本体、配上 `SimContext` 撮合——和
+//! `examples/snipe.rs` 实盘跑的完全是同一套策略代码，回测只是把行情来源换成了
+//! 录制文件，而不是另外攒一份简化逻辑。
+//!
+//! 运行方式：
+//! ```bash
+//! cargo run --example backtest_snipe -- recorded_book.jsonl
+//! ```
+
+use polyfill_rs::backtest::{self, ReplaySpeed};
+use polyfill_rs::errors::Result;
+use polyfill_rs::fill::FillEngine;
+use polyfill_rs::sim_context::SimContext;
+use polyfill_rs::snipe::SnipeStrategy;
+use polyfill_rs::stats::SnipeStats;
+use polyfill_rs::store::PersistentStore;
+use polyfill_rs::strategy::{self, StrategyParamManager, StrategyParams};
+use polyfill_rs::types::StreamMessage;
+use rust_decimal_macros::dec;
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+use tracing::info;
+
+fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let path = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("recorded_book.jsonl"));
+
+    let events = backtest::load_jsonl(&path)?;
+    info!("已加载 {} 条录制事件，来自 {:?}", events.len(), path);
+
+    // 回测用独立的参数文件/检查点，不和 `examples/snipe.rs` 的实盘状态混在一起
+    let params = StrategyParamManager::load_or_create(
+        "backtest_snipe_params.json",
+        StrategyParams {
+            max_spread_pct: dec!(2.0),
+            min_order_size: dec!(10),
+            max_order_size: dec!(100),
+            stale_threshold: 5,
+        },
+    )?;
+    let store = PersistentStore::new("backtest_snipe_checkpoint.json");
+    let mut strategy = SnipeStrategy::new("12345".to_string(), params, store)?;
+
+    let mut ctx = SimContext::new(
+        100,
+        FillEngine::new(
+            dec!(10), // 最小订单大小
+            dec!(2.0), // 2% 最大滑点容忍度
+            5,         // 5 bps 费用率
+        ),
+    );
+
+    // `on_update` 需要可变借用 `strategy` 来 dispatch，`snapshot_stats` 又要读它的统计
+    // 数据，两个闭包在同一次 `replay` 调用里同时存活，不能都直接捕获 `strategy`。
+    // 改成在 `on_update` 末尾把最新统计写进一个共享的 `Rc<RefCell<_>>`，`snapshot_stats`
+    // 只读这份共享状态的克隆，不再需要借用 `strategy` 本身。
+    let latest_stats = Rc::new(RefCell::new(SnipeStats::default()));
+    let latest_stats_for_snapshot = Rc::clone(&latest_stats);
+
+    let report = backtest::replay(
+        events,
+        ReplaySpeed::Instant,
+        |message| {
+            // 执行上下文先拿到同一份行情，保持它的订单簿镜像与策略一致
+            if let StreamMessage::BookUpdate { data } = &message {
+                ctx.apply_delta(data.clone())?;
+            }
+            strategy::dispatch(&mut strategy, &mut ctx, message)?;
+            *latest_stats.borrow_mut() = strategy.get_stats().clone();
+            Ok(())
+        },
+        || latest_stats_for_snapshot.borrow().clone(),
+    )?;
+
+    info!("回测完成:");
+    info!("  总成交量: {}", report.stats.total_volume);
+    info!("  总盈亏: {}", report.stats.total_pnl);
+    info!("  最大回撤: {}", report.max_drawdown);
+    info!("  资金曲线样本数: {}", report.equity_curve.len());
+
+    Ok(())
+}
@@ -0,0 +1,210 @@
+//! Grid trading example for polyfill-rs
+//!
+//! 这个示例演示了网格交易策略：
+//! - 在起始价位两侧按固定间距铺开一条买单梯子
+//! - 本地维护"虚拟挂单"，只把离盘口最近的几层暴露成真实订单，避免挂单数超过交易所限制
+//! - 买单成交后挂出对应的卖单吃价差，卖单成交后这一层重新回到等待买入
+//! - 价格跌出/涨出网格带宽时自动止损清仓，或者（开启 `moving_grid` 时）把整条梯子
+//!   重新围绕当前价铺开，而不是被单边行情甩在身后
+//!
+//! ## 策略框架
+//! `GridStrategy` 实现了 `polyfill_rs::strategy::Strategy`，核心的梯子状态和触发逻辑
+//! 都在 `polyfill_rs::grid::GridEngine` 里；下单这一副作用通过 `StrategyContext` 发起，
+//! 和 `examples/snipe.rs` 里的分工完全一样。
+
+use polyfill_rs::{
+    errors::Result,
+    grid::{GridConfig, GridEngine},
+    sim_context::SimContext,
+    strategy::{self, Strategy, StrategyContext},
+    types::*,
+};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{error, info};
+
+/// 网格交易策略
+///
+/// 只负责把行情消息转给 `GridEngine::on_book_update`/`on_trade`，真正的盘口镜像、梯子
+/// 状态、触发、止损和移动网格逻辑都在 `GridEngine` 里。
+pub struct GridStrategy {
+    token_id: String,
+    engine: GridEngine,
+}
+
+impl GridStrategy {
+    /// 创建一个新的网格策略实例
+    ///
+    /// # 参数
+    /// - `token_id`: 要交易的市场代币 ID
+    /// - `config`: 网格的静态形状（层数、间距、每层数量、卖出价差等）
+    /// - `first_price`: 梯子的起始中枢价，可以手动指定，也可以用自动探测到的最新成交价
+    pub fn new(token_id: String, config: GridConfig, first_price: Decimal) -> Self {
+        let engine = GridEngine::new(token_id.clone(), config, first_price);
+        Self { token_id, engine }
+    }
+
+    /// 网格已实现盈亏（按完整买卖回合累计）
+    pub fn realized_pnl(&self) -> Decimal {
+        self.engine.realized_pnl
+    }
+
+    /// 已完成的买卖回合数
+    pub fn round_trips(&self) -> u64 {
+        self.engine.round_trips
+    }
+
+    /// 是否已经触发止损并停止工作
+    pub fn halted(&self) -> bool {
+        self.engine.halted
+    }
+}
+
+impl Strategy for GridStrategy {
+    fn on_book_update(&mut self, ctx: &mut dyn StrategyContext, delta: OrderDelta) -> Result<()> {
+        if delta.token_id != self.token_id {
+            return Ok(());
+        }
+        // `GridEngine` 自己维护订单簿镜像，从更新后的盘口中间价驱动触发检查，而不是
+        // 直接拿这一条增量自己的 `price`（那只是挂单深度的一侧，不是成交/中间价）
+        self.engine.on_book_update(ctx, delta)
+    }
+
+    fn on_trade(&mut self, ctx: &mut dyn StrategyContext, fill: FillEvent) -> Result<()> {
+        if fill.token_id != self.token_id {
+            return Ok(());
+        }
+        self.engine.on_trade(ctx, fill.price)
+    }
+
+    fn on_heartbeat(&mut self, _ctx: &mut dyn StrategyContext, _timestamp: u64) -> Result<()> {
+        if self.engine.halted {
+            info!("{} 的网格已止损停止，等待人工介入", self.token_id);
+        }
+        Ok(())
+    }
+
+    fn on_timer(&mut self, _ctx: &mut dyn StrategyContext, timer_id: &str) -> Result<()> {
+        info!("定时器触发: {}", timer_id);
+        Ok(())
+    }
+}
+
+/// 模拟市场数据生成器（用于测试和演示）
+///
+/// 带一个缓慢的趋势漂移，这样示例里既能看到网格正常的买卖回合，偶尔也能看到价格
+/// 走出带宽触发止损/移动网格。
+struct MockMarketData {
+    token_id: String,
+    price: Decimal,
+    drift: Decimal,
+    volatility: Decimal,
+    sequence: u64,
+}
+
+impl MockMarketData {
+    fn new(token_id: String, start_price: Decimal) -> Self {
+        Self {
+            token_id,
+            price: start_price,
+            drift: dec!(0.0005), // 每条消息 0.05% 的趋势漂移
+            volatility: dec!(0.002), // 0.2% 波动率
+            sequence: 0,
+        }
+    }
+
+    fn generate_update(&mut self) -> StreamMessage {
+        self.sequence += 1;
+
+        let random_factor = Decimal::from(rand::random::<i64>() % 100 - 50) / Decimal::from(100);
+        let noise = random_factor * Decimal::from(2) * self.volatility;
+        self.price *= Decimal::from(1) + self.drift + noise;
+
+        let side = if rand::random::<bool>() {
+            Side::BUY
+        } else {
+            Side::SELL
+        };
+        let size = Decimal::from(rand::random::<u64>() % 1000 + 100);
+
+        StreamMessage::BookUpdate {
+            data: OrderDelta {
+                token_id: self.token_id.clone(),
+                timestamp: chrono::Utc::now(),
+                side,
+                price: self.price,
+                size,
+                sequence: self.sequence,
+            },
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    info!("启动网格交易示例...");
+
+    let token_id = "12345".to_string();
+    let first_price = dec!(0.5); // 手动指定起始中枢价；也可以改成从第一条行情自动探测
+
+    let config = GridConfig {
+        all_num: 10,             // 总共 10 层买单
+        price_grid: dec!(0.01),  // 每层间距 0.01
+        amount_once: dec!(20),   // 每层 20 份
+        price_diff: dec!(0.005), // 成交后卖出价差 0.005
+        visible_levels: 3,       // 只暴露离盘口最近的 3 层
+        moving_grid: true,       // 突破带宽时重新围绕当前价铺开，而不是直接止损
+    };
+
+    let mut strategy = GridStrategy::new(token_id.clone(), config, first_price);
+
+    let mut ctx = SimContext::new(
+        100,
+        polyfill_rs::fill::FillEngine::new(
+            dec!(10),  // 最小订单大小
+            dec!(2.0), // 2% 最大滑点容忍度
+            5,         // 5 bps 费用率
+        ),
+    );
+
+    let mut market_data = MockMarketData::new(token_id, first_price);
+
+    let mut message_count = 0;
+    let max_messages = 200;
+
+    while message_count < max_messages {
+        let update = market_data.generate_update();
+
+        if let StreamMessage::BookUpdate { data } = &update {
+            ctx.apply_delta(data.clone())?;
+        }
+
+        if let Err(e) = strategy::dispatch(&mut strategy, &mut ctx, update) {
+            error!("处理更新时出错: {}", e);
+        }
+
+        if message_count % 20 == 0 {
+            info!(
+                "统计: {} 次回合, 已实现盈亏 {}, 止损状态: {}",
+                strategy.round_trips(),
+                strategy.realized_pnl(),
+                strategy.halted()
+            );
+        }
+
+        message_count += 1;
+        sleep(Duration::from_millis(50)).await;
+    }
+
+    info!("最终统计:");
+    info!("  完成回合数: {}", strategy.round_trips());
+    info!("  已实现盈亏: {}", strategy.realized_pnl());
+    info!("  是否止损停止: {}", strategy.halted());
+
+    info!("网格交易示例完成！");
+    Ok(())
+}
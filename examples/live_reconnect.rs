@@ -0,0 +1,72 @@
+//! 断线自动重连示例
+//!
+//! `polyfill_rs::resilience::ReconnectingStream` 把 `WebSocketStream` 包了一层：连接
+//! 掉线后按 `RetryPolicy` 的退避曲线重新建连，并自动重新订阅之前订阅过的频道。这个示例
+//! 直接驱动它本身，而不是通过某个策略间接用到——`connect` 建立初次连接、`subscribe_market_channel`
+//! 订阅频道，读循环里一旦 `next_message` 返回 `None`（连接已断开）就调用 `reconnect`，
+//! 重连期间可能漏收的增量交给 `resilience::SequenceTracker` 检测，发现缺口就通过
+//! `resilience::resync` 触发一次本地订单簿全量重建。
+//!
+//! 运行方式：
+//! ```bash
+//! cargo run --example live_reconnect
+//! ```
+
+use polyfill_rs::book::OrderBookManager;
+use polyfill_rs::errors::Result;
+use polyfill_rs::resilience::{
+    resync, ReconnectingStream, RetryPolicy, SequenceCheck, SequenceTracker,
+};
+use polyfill_rs::types::StreamMessage;
+use std::time::Duration;
+use tracing::{info, warn};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let token_id = "12345".to_string();
+    let retry_policy = RetryPolicy::new(5, Duration::from_millis(200), Duration::from_secs(10));
+
+    let mut stream = ReconnectingStream::new("wss://clob.polymarket.com/ws", retry_policy);
+    stream.connect().await?;
+    stream.subscribe_market_channel(vec![token_id.clone()]).await?;
+
+    let mut book_manager = OrderBookManager::new(100);
+    book_manager.get_or_create_book(&token_id)?;
+    let mut sequence_tracker = SequenceTracker::new();
+
+    let mut message_count = 0;
+    let max_messages = 100;
+
+    while message_count < max_messages {
+        match stream.next_message().await {
+            Some(Ok(StreamMessage::BookUpdate { data })) => {
+                match sequence_tracker.check(&data) {
+                    SequenceCheck::Ok => book_manager.apply_delta(data)?,
+                    SequenceCheck::Gap { expected, got } => {
+                        warn!("序列号缺口: 期望 {}，实际 {}", expected, got);
+                        resync(&mut book_manager, &token_id)?;
+                    }
+                }
+                message_count += 1;
+            }
+            Some(Ok(_)) => {
+                message_count += 1;
+            }
+            Some(Err(err)) => {
+                warn!("读取行情失败: {}，尝试重连", err);
+                stream.reconnect().await?;
+                sequence_tracker.reset(&token_id);
+            }
+            None => {
+                info!("连接已断开，尝试重连");
+                stream.reconnect().await?;
+                sequence_tracker.reset(&token_id);
+            }
+        }
+    }
+
+    info!("已处理 {} 条消息，示例结束", message_count);
+    Ok(())
+}
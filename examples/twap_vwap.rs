@@ -0,0 +1,108 @@
+//! TWAP/VWAP 母单拆分执行示例
+//!
+//! `execute_snipe_order`（见 `polyfill_rs::snipe::SnipeStrategy`）会把整张母单一次性打到
+//! 盘口上，这对流动性较差的 Polymarket 代币会造成明显的市场冲击。这个示例演示用
+//! `fill::Executor` 把一张母单拆成若干子单，按 TWAP 节奏释放，每个节点重新读一次当时的
+//! 盘口（这里用模拟的流动性补充代替真实行情），未成交的差额滚入最后一个节点，最后汇总
+//! 出实际成交均价相对基准价的滑点。
+
+use polyfill_rs::book::{OrderBook, OrderBookManager};
+use polyfill_rs::errors::Result;
+use polyfill_rs::fill::{ExecutionMode, Executor, FillEngine};
+use polyfill_rs::types::{OrderDelta, Side};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::time::Duration;
+use tracing::info;
+
+/// 从 `OrderBookManager` 里拷出一份独立的 `OrderBook` 快照，供 `Executor` 撮合使用
+fn snapshot_book(book_manager: &OrderBookManager, token_id: &str) -> Result<OrderBook> {
+    let book = book_manager.get_book(token_id)?;
+    let mut snapshot = OrderBook::new(token_id.to_string(), 100);
+    for (sequence, level) in book.bids.iter().enumerate() {
+        snapshot.apply_delta(OrderDelta {
+            token_id: token_id.to_string(),
+            timestamp: chrono::Utc::now(),
+            side: Side::BUY,
+            price: level.price,
+            size: level.size,
+            sequence: sequence as u64,
+        })?;
+    }
+    for (sequence, level) in book.asks.iter().enumerate() {
+        snapshot.apply_delta(OrderDelta {
+            token_id: token_id.to_string(),
+            timestamp: chrono::Utc::now(),
+            side: Side::SELL,
+            price: level.price,
+            size: level.size,
+            sequence: sequence as u64,
+        })?;
+    }
+    Ok(snapshot)
+}
+
+fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let token_id = "12345".to_string();
+    let mut book_manager = OrderBookManager::new(100);
+    book_manager.get_or_create_book(&token_id)?;
+
+    // 母单：买入 500 份，对于这个模拟盘口的流动性来说是一张相当大的单子
+    let parent = polyfill_rs::types::MarketOrderRequest {
+        token_id: token_id.clone(),
+        side: Side::BUY,
+        amount: dec!(500),
+        slippage_tolerance: None,
+        client_id: None,
+    };
+
+    let executor_mode = ExecutionMode::Twap {
+        slices: 5,
+        window: Duration::from_secs(50),
+    };
+
+    let mut executor = Executor::new(
+        FillEngine::new(
+            dec!(10), // 最小订单大小
+            dec!(2.0), // 2% 最大滑点容忍度
+            5,         // 5 bps 费用率
+        ),
+        dec!(0.2), // 单个子单最多吃掉 20% 的可见流动性
+    );
+
+    let slices = executor.schedule(&parent, &executor_mode);
+    info!("母单拆成 {} 个子单", slices.len());
+
+    let mut sequence = 0u64;
+    let reports = executor.execute_schedule(&slices, parent.side, &token_id, |_slice| {
+        // 每个节点释放前，模拟盘口上补充一批新的卖单流动性（真实场景中这里应该是
+        // 读取最新的 OrderBookManager 快照，而不是手动灌数据）
+        sequence += 1;
+        book_manager.apply_delta(OrderDelta {
+            token_id: token_id.clone(),
+            timestamp: chrono::Utc::now(),
+            side: Side::SELL,
+            price: dec!(0.5) + Decimal::from(sequence) * dec!(0.001),
+            size: dec!(80),
+            sequence,
+        })?;
+        snapshot_book(&book_manager, &token_id)
+    })?;
+
+    let report = executor.summarize(reports, parent.side);
+
+    info!("执行完毕:");
+    for slice in &report.slices {
+        info!(
+            "  子单 {}: 计划 {}, 成交 {} @ {} (节点中间价 {})",
+            slice.index, slice.planned_size, slice.filled_size, slice.average_price, slice.node_price
+        );
+    }
+    info!("  实际成交均价: {}", report.realized_avg_price);
+    info!("  基准价: {}", report.benchmark_price);
+    info!("  相对基准滑点: {}", report.slippage_vs_benchmark);
+
+    Ok(())
+}
@@ -0,0 +1,363 @@
+//! TWAP/VWAP 母单执行调度器
+//!
+//! `execute_snipe_order` 这样的策略代码会把整张母单一次性打到盘口上，这对于流动性较差的
+//! Polymarket 代币来说会造成明显的市场冲击。`Executor` 把一张大的 `MarketOrderRequest`
+//! 切成若干按时间节点释放的子单，每个子单仍然经 `FillEngine::execute_market_order` 路由到
+//! 当前盘口成交，从而把冲击摊薄到执行窗口内。
+//!
+//! 支持两种模式：
+//! - TWAP：把母单数量 `Q` 平均分成 `N` 份 `q = Q/N`，在窗口内等间隔的时间节点释放，
+//!   基准价为简单时间均价 `(Σ price_i)/n`。
+//! - VWAP：按一条归一化的权重向量 `w_i`（通常是历史盘口成交量估计出的 U 形曲线，且
+//!   `Σ w_i = 1`）分配子单数量 `q_i = Q * w_i`，基准价为成交量加权均价
+//!   `(Σ price_i·volume_i)/(Σ volume_i)`。
+//!
+//! `schedule` 只负责算出每个节点该放多少量；真正按顺序把子单打出去、把未成交差额滚到
+//! 最后一个节点的是 `execute_schedule`。基准价不是拿子单自己的成交均价去加权（那样一个
+//! 被参与率上限压得几乎吃不到量的节点，会把基准拖向一个根本不代表市场水平的价格），
+//! 而是在每个节点释放时单独采样一次盘口中间价（`node_price`），两者独立。
+
+use crate::book::OrderBook;
+use crate::errors::Result;
+use crate::fill::FillEngine;
+use crate::types::{MarketOrderRequest, Side};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::time::Duration;
+
+/// 母单的拆分策略
+#[derive(Debug, Clone)]
+pub enum ExecutionMode {
+    /// 时间加权平均价格：`slices` 份等量子单，均匀分布在 `window` 内
+    Twap { slices: u32, window: Duration },
+    /// 成交量加权平均价格：按 `weights`（须归一化，即求和为 1）分配子单数量，
+    /// 节点同样均匀分布在 `window` 内
+    Vwap {
+        weights: Vec<Decimal>,
+        window: Duration,
+    },
+}
+
+/// 调度好但尚未执行的一个子单
+#[derive(Debug, Clone)]
+pub struct ScheduledSlice {
+    /// 子单在母单中的序号（从 0 开始）
+    pub index: usize,
+    /// 相对母单起始时间的释放偏移
+    pub offset: Duration,
+    /// 本子单计划释放的数量（Q * w_i，或 Q / N）
+    pub planned_size: Decimal,
+    /// 该子单对应的成交量权重（TWAP 下为 1/N，VWAP 下为 w_i），用于计算 VWAP 基准
+    pub weight: Decimal,
+}
+
+/// 单个子单的执行结果
+#[derive(Debug, Clone)]
+pub struct SliceReport {
+    pub index: usize,
+    /// 计划数量：原始调度数量加上从前面节点滚过来的未成交差额
+    pub planned_size: Decimal,
+    /// 实际成交数量（可能小于 planned_size，非最后节点的差额会滚入最后一个节点）
+    pub filled_size: Decimal,
+    pub average_price: Decimal,
+    pub weight: Decimal,
+    /// 释放这个子单时独立采样到的盘口中间价（不是成交均价），作为基准价的输入，
+    /// 这样即使这一节点因为流动性不足被参与率上限裁剪到接近零，基准价也不会被
+    /// 拖向一个不代表市场真实水平的成交价
+    pub node_price: Decimal,
+}
+
+/// 整张母单执行完毕后的汇总报告
+#[derive(Debug, Clone)]
+pub struct ExecutionReport {
+    pub slices: Vec<SliceReport>,
+    /// 实际成交均价（按各子单成交量加权）
+    pub realized_avg_price: Decimal,
+    /// 对应模式的基准价（TWAP 为时间均价，VWAP 为成交量加权均价）
+    pub benchmark_price: Decimal,
+    /// 相对基准的滑点，正值表示比基准差（买单成交价更高 / 卖单成交价更低）
+    pub slippage_vs_benchmark: Decimal,
+}
+
+/// TWAP/VWAP 执行器：把母单拆分为按时间释放的子单，逐一路由给 `FillEngine`
+#[derive(Debug)]
+pub struct Executor {
+    fill_engine: FillEngine,
+    /// 单个子单允许吃掉的对手盘可见流动性上限（参与率），例如 0.2 代表最多吃 20%
+    max_participation_rate: Decimal,
+}
+
+impl Executor {
+    pub fn new(fill_engine: FillEngine, max_participation_rate: Decimal) -> Self {
+        Self {
+            fill_engine,
+            max_participation_rate,
+        }
+    }
+
+    /// 根据执行模式把母单数量拆成各个时间节点的子单计划
+    pub fn schedule(&self, parent: &MarketOrderRequest, mode: &ExecutionMode) -> Vec<ScheduledSlice> {
+        match mode {
+            ExecutionMode::Twap { slices, window } => {
+                let slices = (*slices).max(1);
+                let weight = Decimal::ONE / Decimal::from(slices);
+                let slice_size = parent.amount / Decimal::from(slices);
+                let step = *window / slices;
+                (0..slices)
+                    .map(|i| ScheduledSlice {
+                        index: i as usize,
+                        offset: step * i,
+                        planned_size: slice_size,
+                        weight,
+                    })
+                    .collect()
+            }
+            ExecutionMode::Vwap { weights, window } => {
+                let n = weights.len().max(1) as u32;
+                let step = *window / n;
+                weights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, w)| ScheduledSlice {
+                        index: i,
+                        offset: step * i as u32,
+                        planned_size: parent.amount * *w,
+                        weight: *w,
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// 执行一个子单：按参与率上限裁剪数量后路由给 `FillEngine`。`planned_size` 可能已经
+    /// 包含了前面节点滚过来的未成交差额（见 [`Executor::execute_schedule`]）。
+    pub fn execute_slice(
+        &mut self,
+        slice: &ScheduledSlice,
+        side: Side,
+        token_id: &str,
+        book: &OrderBook,
+    ) -> Result<SliceReport> {
+        let visible_liquidity: Decimal = match side {
+            Side::BUY => book.asks.iter().map(|l| l.size).sum(),
+            Side::SELL => book.bids.iter().map(|l| l.size).sum(),
+        };
+        let cap = visible_liquidity * self.max_participation_rate;
+        let capped_size = slice.planned_size.min(cap.max(dec!(0)));
+
+        let request = MarketOrderRequest {
+            token_id: token_id.to_string(),
+            side,
+            amount: capped_size,
+            slippage_tolerance: None,
+            client_id: Some(format!("twap_vwap_{}", slice.index)),
+        };
+
+        let result = self.fill_engine.execute_market_order(&request, book)?;
+
+        Ok(SliceReport {
+            index: slice.index,
+            planned_size: slice.planned_size,
+            filled_size: result.total_size,
+            average_price: result.average_price,
+            weight: slice.weight,
+            node_price: mid_price(book),
+        })
+    }
+
+    /// 按顺序执行一整组调度好的子单：每个节点执行前用 `next_book` 取一次当时的盘口快照，
+    /// 未成交的差额会累加起来，并入最后一个节点的 `planned_size`——而不是被直接吃掉、
+    /// 凭空消失。
+    pub fn execute_schedule(
+        &mut self,
+        slices: &[ScheduledSlice],
+        side: Side,
+        token_id: &str,
+        mut next_book: impl FnMut(&ScheduledSlice) -> Result<OrderBook>,
+    ) -> Result<Vec<SliceReport>> {
+        let last = slices.len().saturating_sub(1);
+        let mut carry_over = dec!(0);
+        let mut reports = Vec::with_capacity(slices.len());
+
+        for (i, slice) in slices.iter().enumerate() {
+            let mut effective = slice.clone();
+            if i == last {
+                effective.planned_size += carry_over;
+            }
+
+            let book = next_book(slice)?;
+            let report = self.execute_slice(&effective, side, token_id, &book)?;
+
+            if i != last {
+                carry_over += (report.planned_size - report.filled_size).max(dec!(0));
+            }
+            reports.push(report);
+        }
+
+        Ok(reports)
+    }
+
+    /// 汇总所有子单报告，计算实际成交均价、对应基准价与相对基准的滑点
+    pub fn summarize(&self, reports: Vec<SliceReport>, side: Side) -> ExecutionReport {
+        let filled_total: Decimal = reports.iter().map(|r| r.filled_size).sum();
+        let realized_avg_price = if filled_total > dec!(0) {
+            reports
+                .iter()
+                .map(|r| r.average_price * r.filled_size)
+                .sum::<Decimal>()
+                / filled_total
+        } else {
+            dec!(0)
+        };
+
+        // TWAP 基准：各节点独立采样价格的简单均值；VWAP 基准：按权重（代表成交量占比）
+        // 加权均值。两者都用 `node_price`，而不是这个子单自己的成交均价——否则被参与率
+        // 上限裁到接近零的子单，会用一个根本没怎么成交的价格把基准拖歪。
+        let weight_total: Decimal = reports.iter().map(|r| r.weight).sum();
+        let benchmark_price = if weight_total > dec!(0) {
+            reports
+                .iter()
+                .map(|r| r.node_price * r.weight)
+                .sum::<Decimal>()
+                / weight_total
+        } else {
+            dec!(0)
+        };
+
+        let slippage_vs_benchmark = match side {
+            Side::BUY => realized_avg_price - benchmark_price,
+            Side::SELL => benchmark_price - realized_avg_price,
+        };
+
+        ExecutionReport {
+            slices: reports,
+            realized_avg_price,
+            benchmark_price,
+            slippage_vs_benchmark,
+        }
+    }
+}
+
+/// 盘口中间价：最优买卖价的均值，缺一边就退化成另一边的最优价
+fn mid_price(book: &OrderBook) -> Decimal {
+    match (book.bids.first(), book.asks.first()) {
+        (Some(bid), Some(ask)) => (bid.price + ask.price) / dec!(2),
+        (Some(bid), None) => bid.price,
+        (None, Some(ask)) => ask.price,
+        (None, None) => dec!(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fill::FillEngine;
+    use crate::types::OrderDelta;
+
+    fn test_executor() -> Executor {
+        Executor::new(FillEngine::new(dec!(1), dec!(5.0), 5), dec!(0.2))
+    }
+
+    fn parent(amount: Decimal) -> MarketOrderRequest {
+        MarketOrderRequest {
+            token_id: "token".to_string(),
+            side: Side::BUY,
+            amount,
+            slippage_tolerance: None,
+            client_id: None,
+        }
+    }
+
+    fn level_delta(token_id: &str, side: Side, price: Decimal, size: Decimal, sequence: u64) -> OrderDelta {
+        OrderDelta {
+            token_id: token_id.to_string(),
+            timestamp: chrono::Utc::now(),
+            side,
+            price,
+            size,
+            sequence,
+        }
+    }
+
+    #[test]
+    fn twap_splits_into_equal_weighted_slices_on_an_even_schedule() {
+        let executor = test_executor();
+        let slices = executor.schedule(
+            &parent(dec!(100)),
+            &ExecutionMode::Twap {
+                slices: 4,
+                window: Duration::from_secs(40),
+            },
+        );
+
+        assert_eq!(slices.len(), 4);
+        for (i, slice) in slices.iter().enumerate() {
+            assert_eq!(slice.index, i);
+            assert_eq!(slice.planned_size, dec!(25));
+            assert_eq!(slice.weight, dec!(0.25));
+            assert_eq!(slice.offset, Duration::from_secs(10) * i as u32);
+        }
+    }
+
+    #[test]
+    fn vwap_weights_slices_by_the_supplied_volume_profile() {
+        let executor = test_executor();
+        let weights = vec![dec!(0.2), dec!(0.5), dec!(0.3)];
+        let slices = executor.schedule(
+            &parent(dec!(200)),
+            &ExecutionMode::Vwap {
+                weights: weights.clone(),
+                window: Duration::from_secs(30),
+            },
+        );
+
+        assert_eq!(slices.len(), 3);
+        for (slice, w) in slices.iter().zip(weights.iter()) {
+            assert_eq!(slice.planned_size, dec!(200) * *w);
+            assert_eq!(slice.weight, *w);
+        }
+    }
+
+    #[test]
+    fn mid_price_averages_best_bid_and_ask() {
+        let mut book = OrderBook::new("token".to_string(), 10);
+        book.apply_delta(level_delta("token", Side::BUY, dec!(0.40), dec!(5), 1))
+            .unwrap();
+        book.apply_delta(level_delta("token", Side::SELL, dec!(0.60), dec!(5), 2))
+            .unwrap();
+
+        assert_eq!(mid_price(&book), dec!(0.50));
+    }
+
+    #[test]
+    fn mid_price_falls_back_to_the_only_side_present() {
+        let mut book = OrderBook::new("token".to_string(), 10);
+        book.apply_delta(level_delta("token", Side::BUY, dec!(0.40), dec!(5), 1))
+            .unwrap();
+
+        assert_eq!(mid_price(&book), dec!(0.40));
+    }
+
+    #[test]
+    fn node_price_is_sampled_independently_of_the_slices_own_fill() {
+        // 这一单的计划量远超可见流动性，会被参与率上限裁到接近零，但 node_price 仍然
+        // 应该反映釋放时刻真实的盘口中间价，而不是跟着被压低的成交价一起失真
+        let mut executor = test_executor();
+        let mut book = OrderBook::new("token".to_string(), 10);
+        book.apply_delta(level_delta("token", Side::SELL, dec!(0.50), dec!(1), 1))
+            .unwrap();
+
+        let slice = ScheduledSlice {
+            index: 0,
+            offset: Duration::from_secs(0),
+            planned_size: dec!(1000),
+            weight: dec!(1),
+        };
+
+        let report = executor
+            .execute_slice(&slice, Side::BUY, "token", &book)
+            .unwrap();
+
+        assert_eq!(report.node_price, dec!(0.50));
+    }
+}
@@ -0,0 +1,8 @@
+//! 成交/执行相关功能
+//!
+//! 撮合引擎本身（`FillEngine`/`FillResult`/`FillStatus`）定义在这个模块别处；`executor`
+//! 是在它之上新增的 TWAP/VWAP 母单拆分调度器，把一张大单切成若干按时间释放的子单。
+
+pub mod executor;
+
+pub use executor::{ExecutionMode, ExecutionReport, Executor, ScheduledSlice, SliceReport};
@@ -0,0 +1,540 @@
+//! 网格交易引擎
+//!
+//! 在起始价位（可以手动指定，也可以用当前最新成交价自动探测）两侧，按 `price_grid`
+//! 的间距铺开 `all_num` 层买单，每层数量 `amount_once`。交易所对挂单数量通常有上限，
+//! 所以整条梯子只在本地维护成"虚拟挂单"，每次有新的成交价时只检查离当前价最近的
+//! `visible_levels` 层是否被触发——这就相当于只把离盘口最近的几层"暴露"成真实订单。
+//!
+//! 某一层的买单被触发后，立即在实际买入成交均价 `+ price_diff` 挂一个虚拟卖单去吃这段价差；
+//! 卖单被触发后，这一层重新回到等待买入的状态，一次买卖算一次完整的网格"回合"，
+//! 按回合记录已实现盈亏。
+//!
+//! 两个风险控制：价格跌出/涨出网格带宽太多时自动止损清仓并停止整个网格；如果开启
+//! `moving_grid`，遇到单边突破时改为把整条梯子重新围绕当前价格铺开，而不是被单边行情
+//! 甩在身后变成一堆永远触发不到的死单。
+
+use crate::book::OrderBookManager;
+use crate::errors::Result;
+use crate::strategy::StrategyContext;
+use crate::types::{MarketOrderRequest, OrderDelta, Side};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tracing::{info, warn};
+
+/// 网格策略的静态配置
+#[derive(Debug, Clone)]
+pub struct GridConfig {
+    /// 总层数（买单总数，不含卖单）
+    pub all_num: u32,
+    /// 相邻两层之间的价格间距
+    pub price_grid: Decimal,
+    /// 每层的下单数量
+    pub amount_once: Decimal,
+    /// 买单成交后，挂出的卖单相对买价的价差
+    pub price_diff: Decimal,
+    /// 同时暴露为"可触发"的最近层数，模拟交易所对挂单数量的限制
+    pub visible_levels: usize,
+    /// 价格突破网格带宽时，是否把整条梯子重新围绕当前价铺开，而不是止损离场
+    pub moving_grid: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum LevelState {
+    /// 等待价格跌到 `buy_price` 触发买入
+    WaitingBuy,
+    /// 已买入，等待价格涨到 `sell_price` 触发卖出
+    ///
+    /// `entry_price`/`amount` 记录的是这笔仓位实际成交的均价和数量（来自
+    /// `FillResult`），不是梯子上名义的 `buy_price`/`amount_once`——部分成交或者
+    /// 滑点都会让两者不一致，PnL 和后续平仓必须按实际成交的数字算。
+    WaitingSell {
+        entry_price: Decimal,
+        amount: Decimal,
+        sell_price: Decimal,
+    },
+}
+
+/// 梯子上的一层
+#[derive(Debug, Clone, Copy)]
+pub struct GridLevel {
+    pub buy_price: Decimal,
+    state: LevelState,
+}
+
+impl GridLevel {
+    /// 这一层当前是否持有一笔已买入、等待卖出的仓位
+    pub fn is_holding(&self) -> bool {
+        matches!(self.state, LevelState::WaitingSell { .. })
+    }
+}
+
+/// 网格交易引擎：维护虚拟挂单梯子，驱动买卖触发、止损和移动网格
+pub struct GridEngine {
+    config: GridConfig,
+    token_id: String,
+    levels: Vec<GridLevel>,
+    /// 本地维护的订单簿镜像，用来从 `on_book_update` 收到的增量里推导出中间价
+    book_manager: OrderBookManager,
+    /// 已实现的网格盈亏（按完整回合累计）
+    pub realized_pnl: Decimal,
+    /// 已完成的买卖回合数
+    pub round_trips: u64,
+    /// 触发止损后整个网格停止工作
+    pub halted: bool,
+}
+
+impl GridEngine {
+    /// 以 `first_price` 为中枢铺开梯子。`first_price` 可以是手动指定的起始价，也可以是
+    /// 自动探测到的当前最新成交价。
+    pub fn new(token_id: String, config: GridConfig, first_price: Decimal) -> Self {
+        let levels = Self::build_ladder(first_price, &config);
+        let mut book_manager = OrderBookManager::new(100);
+        let _ = book_manager.get_or_create_book(&token_id);
+        Self {
+            config,
+            token_id,
+            levels,
+            book_manager,
+            realized_pnl: dec!(0),
+            round_trips: 0,
+            halted: false,
+        }
+    }
+
+    /// 铺出恰好 `all_num` 层，以 `center` 为中枢分布在两侧：层数为奇数时严格对称，
+    /// 为偶数时 `center` 下方比上方多一层
+    fn build_ladder(center: Decimal, config: &GridConfig) -> Vec<GridLevel> {
+        let half = config.all_num as i64 / 2;
+        (0..config.all_num)
+            .map(|i| {
+                let offset = i as i64 - half;
+                GridLevel {
+                    buy_price: center + config.price_grid * Decimal::from(offset),
+                    state: LevelState::WaitingBuy,
+                }
+            })
+            .collect()
+    }
+
+    fn band(&self) -> (Decimal, Decimal) {
+        let lowest = self
+            .levels
+            .iter()
+            .map(|l| l.buy_price)
+            .fold(Decimal::MAX, Decimal::min);
+        let highest = self
+            .levels
+            .iter()
+            .map(|l| l.buy_price)
+            .fold(Decimal::MIN, Decimal::max);
+        (lowest, highest)
+    }
+
+    /// 应用一条订单簿增量，用更新后盘口的中间价驱动一次网格检查
+    ///
+    /// 中间价取最优买一/卖一的均值；只有一侧有报价时就用那一侧，两侧都没有（比如这
+    /// 条增量是这个代币收到的第一条）则跳过这一次检查，而不是拿 0 当价格触发止损。
+    pub fn on_book_update(&mut self, ctx: &mut dyn StrategyContext, delta: OrderDelta) -> Result<()> {
+        self.book_manager.apply_delta(delta)?;
+        if let Some(price) = self.mid_price()? {
+            self.on_price(ctx, price)?;
+        }
+        Ok(())
+    }
+
+    /// 用一笔实际成交的价格驱动一次网格检查
+    pub fn on_trade(&mut self, ctx: &mut dyn StrategyContext, price: Decimal) -> Result<()> {
+        self.on_price(ctx, price)
+    }
+
+    fn mid_price(&self) -> Result<Option<Decimal>> {
+        let book = self.book_manager.get_book(&self.token_id)?;
+        Ok(match (book.bids.first(), book.asks.first()) {
+            (Some(bid), Some(ask)) => Some((bid.price + ask.price) / dec!(2)),
+            (Some(bid), None) => Some(bid.price),
+            (None, Some(ask)) => Some(ask.price),
+            (None, None) => None,
+        })
+    }
+
+    /// 用最新成交价驱动一次网格检查：处理突破（止损/移动网格），再检查离当前价最近的
+    /// `visible_levels` 层是否被触发
+    fn on_price(&mut self, ctx: &mut dyn StrategyContext, price: Decimal) -> Result<()> {
+        if self.halted {
+            return Ok(());
+        }
+
+        let (lowest, highest) = self.band();
+        // 价格突破网格带宽再多容忍一个网格间距，超过后要么移动网格，要么止损离场
+        let breakout = price > highest + self.config.price_grid || price < lowest - self.config.price_grid;
+
+        if breakout {
+            if self.config.moving_grid {
+                info!(
+                    "{} 价格 {} 突破网格带宽 [{}, {}]，重新围绕当前价铺开网格",
+                    self.token_id, price, lowest, highest
+                );
+                // 重新铺开梯子会丢弃所有旧的层，包括还持有真实仓位（`WaitingSell`）的
+                // 那些——先按止损的方式把它们平仓记账，不能让这部分仓位随着梯子重建
+                // 一起从引擎的视野里消失，变成账上有货但程序完全不知道的状态
+                self.liquidate_holdings(ctx)?;
+                self.levels = Self::build_ladder(price, &self.config);
+            } else {
+                warn!(
+                    "{} 价格 {} 突破网格带宽 [{}, {}]，触发止损并停止网格",
+                    self.token_id, price, lowest, highest
+                );
+                self.stop_loss(ctx)?;
+            }
+            return Ok(());
+        }
+
+        let mut nearest: Vec<usize> = (0..self.levels.len()).collect();
+        nearest.sort_by_key(|&idx| (self.levels[idx].buy_price - price).abs());
+        nearest.truncate(self.config.visible_levels.max(1));
+
+        for idx in nearest {
+            self.try_trigger(ctx, idx, price)?;
+        }
+
+        Ok(())
+    }
+
+    fn try_trigger(&mut self, ctx: &mut dyn StrategyContext, idx: usize, price: Decimal) -> Result<()> {
+        match self.levels[idx].state {
+            LevelState::WaitingBuy if price <= self.levels[idx].buy_price => {
+                let buy_price = self.levels[idx].buy_price;
+                let result = ctx.place_order(MarketOrderRequest {
+                    token_id: self.token_id.clone(),
+                    side: Side::BUY,
+                    amount: self.config.amount_once,
+                    slippage_tolerance: None,
+                    client_id: Some(format!("grid_buy_{}", idx)),
+                })?;
+
+                // 一股没吃到：这层还在等买入，不往前推进状态
+                if result.total_size <= dec!(0) {
+                    return Ok(());
+                }
+
+                // 用实际成交均价（可能因为滑点跟名义 buy_price 不一样）定卖价和记账入场价
+                let entry_price = result.average_price;
+                let sell_price = entry_price + self.config.price_diff;
+                self.levels[idx].state = LevelState::WaitingSell {
+                    entry_price,
+                    amount: result.total_size,
+                    sell_price,
+                };
+                info!(
+                    "第 {} 层买入成交 {} @ {}，挂出卖单 @ {}",
+                    idx, result.total_size, entry_price, sell_price
+                );
+            }
+            LevelState::WaitingSell {
+                entry_price,
+                amount,
+                sell_price,
+            } if price >= sell_price => {
+                let result = ctx.place_order(MarketOrderRequest {
+                    token_id: self.token_id.clone(),
+                    side: Side::SELL,
+                    amount,
+                    slippage_tolerance: None,
+                    client_id: Some(format!("grid_sell_{}", idx)),
+                })?;
+
+                // 一股没卖掉：仓位原样留在 WaitingSell，下次再试
+                if result.total_size <= dec!(0) {
+                    return Ok(());
+                }
+
+                let closed = result.total_size.min(amount);
+                self.realized_pnl += (result.average_price - entry_price) * closed;
+
+                let remaining = amount - closed;
+                if remaining <= dec!(0) {
+                    self.round_trips += 1;
+                    // 这一层重新回到原来的买价，等待下一次触发
+                    self.levels[idx].state = LevelState::WaitingBuy;
+                    info!(
+                        "第 {} 层卖出成交 {} @ {}，完成一次回合，已实现盈亏 {}",
+                        idx, closed, result.average_price, self.realized_pnl
+                    );
+                } else {
+                    // 只平掉了一部分仓位，剩下的继续等这同一个卖价触发
+                    self.levels[idx].state = LevelState::WaitingSell {
+                        entry_price,
+                        amount: remaining,
+                        sell_price,
+                    };
+                    info!(
+                        "第 {} 层部分卖出 {} @ {}，剩余 {} 继续等待卖出 @ {}，已实现盈亏 {}",
+                        idx, closed, result.average_price, remaining, sell_price, self.realized_pnl
+                    );
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// 把所有持有仓位的层用市价卖出清仓并按实际成交记账，但不停止网格。
+    ///
+    /// 止损和移动网格重新铺开梯子之前都需要先平掉已有仓位，区别只在于前者之后要
+    /// 停止整个网格，后者还要接着铺新的梯子，所以两边共用这一段清仓逻辑。
+    fn liquidate_holdings(&mut self, ctx: &mut dyn StrategyContext) -> Result<()> {
+        for idx in 0..self.levels.len() {
+            let (entry_price, amount) = match self.levels[idx].state {
+                LevelState::WaitingSell {
+                    entry_price,
+                    amount,
+                    ..
+                } => (entry_price, amount),
+                LevelState::WaitingBuy => continue,
+            };
+
+            let result = ctx.place_order(MarketOrderRequest {
+                token_id: self.token_id.clone(),
+                side: Side::SELL,
+                amount,
+                slippage_tolerance: None,
+                client_id: Some(format!("grid_liquidate_{}", idx)),
+            })?;
+
+            if result.total_size <= dec!(0) {
+                // 清仓单也没成交：保留持仓状态，不能假装已经平仓了
+                continue;
+            }
+
+            let closed = result.total_size.min(amount);
+            self.realized_pnl += (result.average_price - entry_price) * closed;
+
+            let remaining = amount - closed;
+            self.levels[idx].state = if remaining <= dec!(0) {
+                LevelState::WaitingBuy
+            } else {
+                LevelState::WaitingSell {
+                    entry_price,
+                    amount: remaining,
+                    sell_price: entry_price + self.config.price_diff,
+                }
+            };
+        }
+        Ok(())
+    }
+
+    /// 止损：把所有持有仓位的层用市价卖出清仓，然后停止整个网格
+    fn stop_loss(&mut self, ctx: &mut dyn StrategyContext) -> Result<()> {
+        self.liquidate_holdings(ctx)?;
+        self.halted = true;
+        Ok(())
+    }
+
+    /// 当前梯子上的各层状态，主要用于监控/展示
+    pub fn levels(&self) -> &[GridLevel] {
+        &self.levels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fill::{FillResult, FillStatus};
+    use std::collections::VecDeque;
+    use std::time::Duration;
+
+    fn test_config(all_num: u32) -> GridConfig {
+        GridConfig {
+            all_num,
+            price_grid: dec!(0.01),
+            amount_once: dec!(10),
+            price_diff: dec!(0.005),
+            visible_levels: 1,
+            moving_grid: false,
+        }
+    }
+
+    fn filled(total_size: Decimal, average_price: Decimal) -> FillResult {
+        FillResult {
+            total_size,
+            average_price,
+            status: FillStatus::Filled,
+        }
+    }
+
+    /// 按脚本依次返回 `place_order` 的结果，顺序对应引擎实际发出的下单顺序
+    struct ScriptedContext {
+        fills: VecDeque<FillResult>,
+    }
+
+    impl ScriptedContext {
+        fn new(fills: Vec<FillResult>) -> Self {
+            Self {
+                fills: fills.into(),
+            }
+        }
+    }
+
+    impl StrategyContext for ScriptedContext {
+        fn place_order(&mut self, _request: MarketOrderRequest) -> Result<FillResult> {
+            Ok(self.fills.pop_front().unwrap_or_else(|| filled(dec!(0), dec!(0))))
+        }
+
+        fn cancel_order(&mut self, _order_id: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn subscribe(&mut self, _token_ids: Vec<String>) -> Result<()> {
+            Ok(())
+        }
+
+        fn query_position(&self, _token_id: &str) -> Decimal {
+            dec!(0)
+        }
+
+        fn add_timer(&mut self, _after: Duration, _timer_id: String) {}
+
+        fn reconnect(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn build_ladder_produces_exactly_all_num_levels_when_even() {
+        let config = test_config(10);
+        let levels = GridEngine::build_ladder(dec!(1.0), &config);
+        assert_eq!(levels.len(), 10);
+    }
+
+    #[test]
+    fn build_ladder_produces_exactly_all_num_levels_when_odd() {
+        let config = test_config(7);
+        let levels = GridEngine::build_ladder(dec!(1.0), &config);
+        assert_eq!(levels.len(), 7);
+        // 奇数层数时梯子严格以 center 为中心对称
+        assert_eq!(levels[3].buy_price, dec!(1.0));
+        assert_eq!(levels[0].buy_price, dec!(1.0) - dec!(3) * config.price_grid);
+        assert_eq!(levels[6].buy_price, dec!(1.0) + dec!(3) * config.price_grid);
+    }
+
+    #[test]
+    fn round_trip_books_pnl_from_actual_fill_prices_not_nominal_ladder_prices() {
+        let config = test_config(1);
+        let mut engine = GridEngine::new("tok".to_string(), config, dec!(1.0));
+        // 买入实际成交价比名义 buy_price 更好（1.0 -> 0.99），卖出实际成交价也和
+        // 触发价不一样（1.0 -> 1.02）——PnL 必须按这两个真实成交价算，而不是
+        // buy_price/price_diff 推出来的名义价格
+        let mut ctx = ScriptedContext::new(vec![filled(dec!(10), dec!(0.99)), filled(dec!(10), dec!(1.02))]);
+
+        engine.on_trade(&mut ctx, dec!(1.0)).unwrap();
+        assert!(engine.levels()[0].is_holding());
+
+        // 触发价只需要 >= sell_price 且不超出网格带宽容忍度，跟最终记账用的成交价无关
+        engine.on_trade(&mut ctx, dec!(1.0)).unwrap();
+
+        assert!(!engine.levels()[0].is_holding());
+        assert_eq!(engine.round_trips, 1);
+        assert_eq!(engine.realized_pnl, dec!(0.3));
+    }
+
+    #[test]
+    fn a_zero_size_fill_does_not_advance_the_level_state() {
+        let config = test_config(1);
+        let mut engine = GridEngine::new("tok".to_string(), config, dec!(1.0));
+        let mut ctx = ScriptedContext::new(vec![filled(dec!(0), dec!(0))]);
+
+        engine.on_trade(&mut ctx, dec!(1.0)).unwrap();
+
+        // 买单没有真的成交，这层应该还在等买入，不能被错误地标记成已持仓
+        assert!(!engine.levels()[0].is_holding());
+        assert_eq!(engine.realized_pnl, dec!(0));
+    }
+
+    #[test]
+    fn partial_fills_on_entry_and_exit_keep_the_remaining_amount_in_waiting_sell() {
+        let config = test_config(1);
+        let mut engine = GridEngine::new("tok".to_string(), config, dec!(1.0));
+        let mut ctx = ScriptedContext::new(vec![
+            filled(dec!(6), dec!(1.0)),  // 买入只成交了 6/10
+            filled(dec!(4), dec!(1.05)), // 第一次卖出只平掉 4/6
+            filled(dec!(2), dec!(1.03)), // 第二次卖出平掉剩下的 2
+        ]);
+
+        engine.on_trade(&mut ctx, dec!(1.0)).unwrap();
+        assert!(engine.levels()[0].is_holding());
+
+        // 第一次卖出触发：只平仓 4 份，剩余 2 份还留在这一层，回合还没算完
+        engine.on_trade(&mut ctx, dec!(1.005)).unwrap();
+        assert!(engine.levels()[0].is_holding());
+        assert_eq!(engine.round_trips, 0);
+        assert_eq!(engine.realized_pnl, dec!(0.2)); // (1.05 - 1.0) * 4
+
+        // 第二次卖出触发：平掉剩下的 2 份，回合完成
+        engine.on_trade(&mut ctx, dec!(1.005)).unwrap();
+        assert!(!engine.levels()[0].is_holding());
+        assert_eq!(engine.round_trips, 1);
+        assert_eq!(engine.realized_pnl, dec!(0.26)); // 0.2 + (1.03 - 1.0) * 2
+    }
+
+    #[test]
+    fn stop_loss_liquidates_holding_levels_at_the_actual_fill_price_and_halts() {
+        let config = test_config(1);
+        let mut engine = GridEngine::new("tok".to_string(), config, dec!(1.0));
+        let mut ctx = ScriptedContext::new(vec![
+            filled(dec!(10), dec!(1.0)),  // 建仓
+            filled(dec!(10), dec!(0.90)), // 止损平仓，实际成交价比入场价低
+        ]);
+
+        engine.on_trade(&mut ctx, dec!(1.0)).unwrap();
+        assert!(engine.levels()[0].is_holding());
+
+        // 价格跌出带宽太多，触发止损（moving_grid = false）
+        engine.on_trade(&mut ctx, dec!(0.5)).unwrap();
+
+        assert!(engine.halted);
+        assert!(!engine.levels()[0].is_holding());
+        assert_eq!(engine.realized_pnl, dec!(-1.0)); // (0.90 - 1.0) * 10
+    }
+
+    #[test]
+    fn moving_grid_liquidates_held_positions_before_rebuilding_the_ladder() {
+        let mut config = test_config(1);
+        config.moving_grid = true;
+        let mut engine = GridEngine::new("tok".to_string(), config, dec!(1.0));
+        let mut ctx = ScriptedContext::new(vec![
+            filled(dec!(10), dec!(1.0)),  // 建仓
+            filled(dec!(10), dec!(1.15)), // 突破带宽前先被平仓的那一笔
+        ]);
+
+        engine.on_trade(&mut ctx, dec!(1.0)).unwrap();
+        assert!(engine.levels()[0].is_holding());
+
+        // 价格突破带宽太多，moving_grid=true 时不会止损停摆，而是先清仓再重新铺开梯子
+        engine.on_trade(&mut ctx, dec!(1.5)).unwrap();
+
+        assert!(!engine.halted);
+        // 梯子已经重新围绕新价格铺开，旧的持仓层不会继续留在这里
+        assert!(!engine.levels()[0].is_holding());
+        assert_eq!(engine.levels()[0].buy_price, dec!(1.5));
+        // 但旧仓位的盈亏必须被记下来，不能随着梯子重建一起消失
+        assert_eq!(engine.realized_pnl, dec!(1.5)); // (1.15 - 1.0) * 10
+    }
+
+    #[test]
+    fn stop_loss_keeps_the_position_open_if_the_liquidating_order_does_not_fill() {
+        let config = test_config(1);
+        let mut engine = GridEngine::new("tok".to_string(), config, dec!(1.0));
+        let mut ctx = ScriptedContext::new(vec![
+            filled(dec!(10), dec!(1.0)), // 建仓
+            filled(dec!(0), dec!(0)),    // 止损单一股没卖掉
+        ]);
+
+        engine.on_trade(&mut ctx, dec!(1.0)).unwrap();
+        engine.on_trade(&mut ctx, dec!(0.5)).unwrap();
+
+        // 网格还是要停下来（止损决策已经做出），但不能假装仓位已经平掉
+        assert!(engine.halted);
+        assert!(engine.levels()[0].is_holding());
+        assert_eq!(engine.realized_pnl, dec!(0));
+    }
+}
@@ -0,0 +1,495 @@
+//! Tick 转 K 线聚合，以及常用技术指标
+//!
+//! 把 `Trade`/`BookUpdate` 的逐笔行情聚合成固定周期的 OHLCV K 线（滚动环形缓冲区保留
+//! 最近 N 根），再在每根 K 线收盘时增量更新 KDJ、均线、成交量均值这些指标，供策略在
+//! 价差逻辑之外再叠加一层技术面过滤，例如"成交量 > 1.5 倍 N 周期均量，且 K 上穿 D"。
+//!
+//! 所有指标都是增量更新（收盘时吃一根新 K 线），不会重新扫描整个 K 线历史。SMA 和量均值
+//! 维护滚动求和、EMA 直接递推，都是 O(1) 更新；KDJ 的 K/D 是递推平滑，但每次仍在最近 `period` 根的窗口内重新
+//! 取一次最高/最低价。
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::VecDeque;
+
+/// 一根 OHLCV K 线
+#[derive(Debug, Clone, Copy)]
+pub struct Candle {
+    pub open_time: u64,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+}
+
+/// 把逐笔成交聚合成固定周期的 K 线，只保留最近 `capacity` 根（环形缓冲区）
+#[derive(Debug)]
+pub struct CandleAggregator {
+    period_secs: u64,
+    capacity: usize,
+    candles: VecDeque<Candle>,
+    current: Option<Candle>,
+}
+
+impl CandleAggregator {
+    pub fn new(period_secs: u64, capacity: usize) -> Self {
+        Self {
+            period_secs: period_secs.max(1),
+            capacity: capacity.max(1),
+            candles: VecDeque::with_capacity(capacity),
+            current: None,
+        }
+    }
+
+    /// 已收盘的历史 K 线（不含正在聚合中的当前 K 线），按时间升序
+    pub fn candles(&self) -> &VecDeque<Candle> {
+        &self.candles
+    }
+
+    /// 用一笔成交（价格、数量、秒级时间戳）更新当前 K 线。
+    ///
+    /// 如果这笔成交落在了新的周期里，上一根 K 线会被收盘并追加到环形缓冲区，
+    /// 返回值就是刚收盘的那根 K 线；否则返回 `None`。
+    pub fn on_trade(&mut self, price: Decimal, size: Decimal, timestamp_secs: u64) -> Option<Candle> {
+        let bucket_start = (timestamp_secs / self.period_secs) * self.period_secs;
+
+        match &mut self.current {
+            Some(candle) if candle.open_time == bucket_start => {
+                candle.high = candle.high.max(price);
+                candle.low = candle.low.min(price);
+                candle.close = price;
+                candle.volume += size;
+                None
+            }
+            Some(_) => {
+                let closed = self.current.take().unwrap();
+                self.push_closed(closed);
+                self.current = Some(Candle {
+                    open_time: bucket_start,
+                    open: price,
+                    high: price,
+                    low: price,
+                    close: price,
+                    volume: size,
+                });
+                Some(closed)
+            }
+            None => {
+                self.current = Some(Candle {
+                    open_time: bucket_start,
+                    open: price,
+                    high: price,
+                    low: price,
+                    close: price,
+                    volume: size,
+                });
+                None
+            }
+        }
+    }
+
+    fn push_closed(&mut self, candle: Candle) {
+        if self.candles.len() == self.capacity {
+            self.candles.pop_front();
+        }
+        self.candles.push_back(candle);
+    }
+}
+
+/// KDJ 的当前取值
+#[derive(Debug, Clone, Copy)]
+pub struct KdjValue {
+    pub k: Decimal,
+    pub d: Decimal,
+    pub j: Decimal,
+}
+
+/// KDJ 随机指标：RSV 基于最近 `period` 根 K 线的最高/最低价，K/D 做指数平滑
+#[derive(Debug)]
+pub struct Kdj {
+    period: usize,
+    highs: VecDeque<Decimal>,
+    lows: VecDeque<Decimal>,
+    k: Decimal,
+    d: Decimal,
+}
+
+impl Kdj {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            highs: VecDeque::with_capacity(period),
+            lows: VecDeque::with_capacity(period),
+            // K、D 的初始值按惯例取 50（中性）
+            k: dec!(50),
+            d: dec!(50),
+        }
+    }
+
+    /// 用一根新收盘的 K 线更新 KDJ
+    pub fn update(&mut self, candle: &Candle) -> KdjValue {
+        if self.highs.len() == self.period {
+            self.highs.pop_front();
+            self.lows.pop_front();
+        }
+        self.highs.push_back(candle.high);
+        self.lows.push_back(candle.low);
+
+        let highest = self.highs.iter().copied().fold(Decimal::MIN, Decimal::max);
+        let lowest = self.lows.iter().copied().fold(Decimal::MAX, Decimal::min);
+        let range = highest - lowest;
+
+        // 防止最高价等于最低价时除以零：此时行情没有波动，RSV 取中性值 50
+        let rsv = if range > dec!(0) {
+            (candle.close - lowest) / range * dec!(100)
+        } else {
+            dec!(50)
+        };
+
+        self.k = dec!(2) / dec!(3) * self.k + dec!(1) / dec!(3) * rsv;
+        self.d = dec!(2) / dec!(3) * self.d + dec!(1) / dec!(3) * self.k;
+        let j = dec!(3) * self.k - dec!(2) * self.d;
+
+        KdjValue {
+            k: self.k,
+            d: self.d,
+            j,
+        }
+    }
+}
+
+/// 判断 K 是否在这一根上穿了 D（金叉）
+pub fn kdj_crossed_up(prev: KdjValue, curr: KdjValue) -> bool {
+    prev.k <= prev.d && curr.k > curr.d
+}
+
+/// 简单移动平均：最近 `period` 根收盘价的算术平均
+#[derive(Debug)]
+pub struct Sma {
+    period: usize,
+    values: VecDeque<Decimal>,
+    sum: Decimal,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            values: VecDeque::with_capacity(period),
+            sum: dec!(0),
+        }
+    }
+
+    pub fn update(&mut self, value: Decimal) -> Decimal {
+        if self.values.len() == self.period {
+            self.sum -= self.values.pop_front().unwrap();
+        }
+        self.values.push_back(value);
+        self.sum += value;
+        self.sum / Decimal::from(self.values.len() as u64)
+    }
+
+    /// 当前窗口的均值，不把任何新值计算在内。用于需要拿"更新前"的均值去跟即将到来的
+    /// 新值比较的场景（比如判断这一根 K 线相对它之前的均量是不是放量）。窗口还没有
+    /// 任何数据时返回 0。
+    pub fn peek(&self) -> Decimal {
+        if self.values.is_empty() {
+            dec!(0)
+        } else {
+            self.sum / Decimal::from(self.values.len() as u64)
+        }
+    }
+}
+
+/// 指数移动平均：`alpha` 通常取 `2 / (period + 1)`
+#[derive(Debug)]
+pub struct Ema {
+    alpha: Decimal,
+    value: Option<Decimal>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        let period = Decimal::from(period.max(1) as u64);
+        Self {
+            alpha: dec!(2) / (period + dec!(1)),
+            value: None,
+        }
+    }
+
+    pub fn update(&mut self, value: Decimal) -> Decimal {
+        let next = match self.value {
+            Some(prev) => self.alpha * value + (dec!(1) - self.alpha) * prev,
+            None => value,
+        };
+        self.value = Some(next);
+        next
+    }
+}
+
+/// 滚动成交量均值：最近 `period` 根 K 线的平均成交量
+pub type VolumeAverage = Sma;
+
+/// 一次完整的指标快照：一根新收盘 K 线对应的所有指标取值
+#[derive(Debug, Clone, Copy)]
+pub struct IndicatorSnapshot {
+    pub candle: Candle,
+    pub kdj: KdjValue,
+    pub ma: Decimal,
+    pub ema: Decimal,
+    pub volume_avg: Decimal,
+}
+
+/// 把 K 线聚合和一组指标捆在一起，每根 K 线收盘时增量更新一次
+pub struct IndicatorSuite {
+    aggregator: CandleAggregator,
+    kdj: Kdj,
+    ma: Sma,
+    ema: Ema,
+    volume_avg: VolumeAverage,
+    last_kdj: Option<KdjValue>,
+}
+
+impl IndicatorSuite {
+    pub fn new(period_secs: u64, candle_capacity: usize, indicator_period: usize) -> Self {
+        Self {
+            aggregator: CandleAggregator::new(period_secs, candle_capacity),
+            kdj: Kdj::new(indicator_period),
+            ma: Sma::new(indicator_period),
+            ema: Ema::new(indicator_period),
+            volume_avg: VolumeAverage::new(indicator_period),
+            last_kdj: None,
+        }
+    }
+
+    /// 喂一笔成交；只有在它触发了一根 K 线收盘时才会重新计算指标并返回快照
+    pub fn on_trade(
+        &mut self,
+        price: Decimal,
+        size: Decimal,
+        timestamp_secs: u64,
+    ) -> Option<IndicatorSnapshot> {
+        let closed = self.aggregator.on_trade(price, size, timestamp_secs)?;
+
+        let kdj = self.kdj.update(&closed);
+        let ma = self.ma.update(closed.close);
+        let ema = self.ema.update(closed.close);
+        // 这根刚收盘的 K 线应该跟它之前的量均值比较，判断这一根是不是放量；如果先把它
+        // 自己的成交量喂进 `volume_avg` 再拿更新后的结果去比，就是在拿"放量后的均值"去
+        // 判断"是否放量"，稀释了大约 1/period 的信号强度。所以这里先取更新前的均值用于
+        // 这次快照，再用这根 K 线的量去滚动窗口，供下一根比较。
+        let volume_avg = self.volume_avg.peek();
+        self.volume_avg.update(closed.volume);
+        self.last_kdj = Some(kdj);
+
+        Some(IndicatorSnapshot {
+            candle: closed,
+            kdj,
+            ma,
+            ema,
+            volume_avg,
+        })
+    }
+
+    /// 最近一次收盘时算出的 KDJ（用于和下一次的快照比较，判断金叉/死叉）
+    pub fn last_kdj(&self) -> Option<KdjValue> {
+        self.last_kdj
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregator_keeps_accumulating_within_the_same_bucket() {
+        let mut agg = CandleAggregator::new(60, 10);
+        assert!(agg.on_trade(dec!(1.0), dec!(5), 0).is_none());
+        assert!(agg.on_trade(dec!(1.2), dec!(3), 30).is_none());
+        assert!(agg.on_trade(dec!(0.9), dec!(2), 59).is_none());
+
+        // 还没跨到下一个周期，历史 K 线里还没有任何一根收盘
+        assert!(agg.candles().is_empty());
+    }
+
+    #[test]
+    fn a_trade_in_the_next_bucket_closes_the_previous_candle() {
+        let mut agg = CandleAggregator::new(60, 10);
+        agg.on_trade(dec!(1.0), dec!(5), 0);
+        agg.on_trade(dec!(1.2), dec!(3), 30);
+        agg.on_trade(dec!(0.9), dec!(2), 59);
+
+        let closed = agg.on_trade(dec!(1.1), dec!(1), 60).unwrap();
+
+        assert_eq!(closed.open_time, 0);
+        assert_eq!(closed.open, dec!(1.0));
+        assert_eq!(closed.high, dec!(1.2));
+        assert_eq!(closed.low, dec!(0.9));
+        assert_eq!(closed.close, dec!(0.9));
+        assert_eq!(closed.volume, dec!(10));
+        assert_eq!(agg.candles().len(), 1);
+        assert_eq!(agg.candles()[0].open_time, 0);
+    }
+
+    #[test]
+    fn aggregator_drops_the_oldest_candle_once_capacity_is_reached() {
+        let mut agg = CandleAggregator::new(60, 2);
+        for period in 0..4u64 {
+            agg.on_trade(dec!(1.0), dec!(1), period * 60);
+        }
+        // 4 个周期过去了，只应该保留最近 2 根已收盘的 K 线（第 4 根还在聚合中未收盘）
+        assert_eq!(agg.candles().len(), 2);
+        assert_eq!(agg.candles()[0].open_time, 60);
+        assert_eq!(agg.candles()[1].open_time, 120);
+    }
+
+    fn candle(high: Decimal, low: Decimal, close: Decimal) -> Candle {
+        Candle {
+            open_time: 0,
+            open: close,
+            high,
+            low,
+            close,
+            volume: dec!(0),
+        }
+    }
+
+    #[test]
+    fn kdj_rsv_is_neutral_when_the_window_has_zero_range() {
+        let mut kdj = Kdj::new(3);
+        // 最高价等于最低价，没有波动，RSV 应该取中性值 50，K/D 从初始值 50 保持不变
+        let value = kdj.update(&candle(dec!(1.0), dec!(1.0), dec!(1.0)));
+        assert_eq!(value.k, dec!(50));
+        assert_eq!(value.d, dec!(50));
+        assert_eq!(value.j, dec!(50));
+    }
+
+    #[test]
+    fn kdj_k_moves_toward_a_100_rsv_by_one_third_per_step() {
+        let mut kdj = Kdj::new(3);
+        // 单独一根 K 线：high=20, low=10, close=20 -> range=10, rsv=(20-10)/10*100=100
+        let value = kdj.update(&candle(dec!(20), dec!(10), dec!(20)));
+        // k = 2/3 * 50 + 1/3 * 100 = 66.666...7 (rust_decimal 默认精度)
+        assert_eq!(value.k, dec!(2) / dec!(3) * dec!(50) + dec!(1) / dec!(3) * dec!(100));
+        assert_eq!(value.d, dec!(2) / dec!(3) * dec!(50) + dec!(1) / dec!(3) * value.k);
+        assert_eq!(value.j, dec!(3) * value.k - dec!(2) * value.d);
+    }
+
+    #[test]
+    fn kdj_window_only_keeps_the_most_recent_period_candles() {
+        let mut kdj = Kdj::new(2);
+        kdj.update(&candle(dec!(10), dec!(0), dec!(5)));
+        let v2 = kdj.update(&candle(dec!(20), dec!(10), dec!(15)));
+        let v3 = kdj.update(&candle(dec!(100), dec!(50), dec!(75)));
+
+        // 窗口容量是 2：算第三根时，第一根的 low=0 应该已经被淘汰出窗口，
+        // 这一步的最低价只能来自第二根 (low=10)，而不是第一根 (low=0)
+        let expected_rsv3 = (dec!(75) - dec!(10)) / (dec!(100) - dec!(10)) * dec!(100);
+        let expected_k3 = dec!(2) / dec!(3) * v2.k + dec!(1) / dec!(3) * expected_rsv3;
+        let expected_d3 = dec!(2) / dec!(3) * v2.d + dec!(1) / dec!(3) * expected_k3;
+
+        assert_eq!(v3.k, expected_k3);
+        assert_eq!(v3.d, expected_d3);
+        assert_eq!(v3.j, dec!(3) * expected_k3 - dec!(2) * expected_d3);
+    }
+
+    #[test]
+    fn kdj_crossed_up_detects_a_golden_cross() {
+        let prev = KdjValue {
+            k: dec!(40),
+            d: dec!(45),
+            j: dec!(30),
+        };
+        let curr = KdjValue {
+            k: dec!(50),
+            d: dec!(45),
+            j: dec!(60),
+        };
+        assert!(kdj_crossed_up(prev, curr));
+    }
+
+    #[test]
+    fn kdj_crossed_up_is_false_when_k_stays_below_d() {
+        let prev = KdjValue {
+            k: dec!(40),
+            d: dec!(45),
+            j: dec!(30),
+        };
+        let curr = KdjValue {
+            k: dec!(44),
+            d: dec!(45),
+            j: dec!(42),
+        };
+        assert!(!kdj_crossed_up(prev, curr));
+    }
+
+    #[test]
+    fn kdj_crossed_up_is_false_when_k_was_already_above_d() {
+        let prev = KdjValue {
+            k: dec!(50),
+            d: dec!(45),
+            j: dec!(60),
+        };
+        let curr = KdjValue {
+            k: dec!(55),
+            d: dec!(46),
+            j: dec!(72),
+        };
+        assert!(!kdj_crossed_up(prev, curr));
+    }
+
+    #[test]
+    fn sma_is_the_arithmetic_mean_of_the_last_period_values() {
+        let mut sma = Sma::new(3);
+        assert_eq!(sma.update(dec!(1)), dec!(1));
+        assert_eq!(sma.update(dec!(2)), dec!(1.5));
+        assert_eq!(sma.update(dec!(3)), dec!(2));
+        // 第四个值进来后，最早的 1 被挤出窗口：(2+3+4)/3 = 3
+        assert_eq!(sma.update(dec!(4)), dec!(3));
+    }
+
+    #[test]
+    fn ema_first_update_seeds_with_the_raw_value() {
+        let mut ema = Ema::new(4); // alpha = 2 / (4+1) = 0.4
+        assert_eq!(ema.update(dec!(10)), dec!(10));
+    }
+
+    #[test]
+    fn ema_blends_new_value_with_previous_by_alpha() {
+        let mut ema = Ema::new(4); // alpha = 0.4
+        ema.update(dec!(10));
+        let next = ema.update(dec!(20));
+        // next = 0.4*20 + 0.6*10 = 14
+        assert_eq!(next, dec!(14));
+    }
+
+    #[test]
+    fn indicator_suite_only_emits_a_snapshot_when_a_candle_closes() {
+        let mut suite = IndicatorSuite::new(60, 10, 3);
+        assert!(suite.on_trade(dec!(1.0), dec!(5), 0).is_none());
+        assert!(suite.last_kdj().is_none());
+
+        let snapshot = suite.on_trade(dec!(1.1), dec!(2), 60).unwrap();
+        assert_eq!(snapshot.candle.open_time, 0);
+        assert_eq!(snapshot.candle.close, dec!(1.0));
+        // 这是第一根收盘的 K 线，它之前没有任何历史均量可比，所以快照里的 volume_avg
+        // 是"收盘前"的均值（还没有数据，为 0），而不是把这根自己的成交量也算进去之后
+        // 的均值——否则拿它去判断"这一根是否放量"永远会被自己拉高的均值稀释掉
+        assert_eq!(snapshot.volume_avg, dec!(0));
+        assert!(suite.last_kdj().is_some());
+    }
+
+    #[test]
+    fn volume_avg_in_the_snapshot_excludes_the_just_closed_candles_own_volume() {
+        let mut suite = IndicatorSuite::new(60, 10, 3);
+        suite.on_trade(dec!(1.0), dec!(4), 0);
+        let first = suite.on_trade(dec!(1.0), dec!(6), 60).unwrap(); // 第一根收盘，量 4
+        assert_eq!(first.volume_avg, dec!(0)); // 之前没有任何历史数据
+
+        let second = suite.on_trade(dec!(1.0), dec!(1), 120).unwrap(); // 第二根收盘，量 6
+        // 第二根的 volume_avg 应该只看到第一根的量 (4)，不应该把它自己的 6 混进去——
+        // 否则一根异常放量的 K 线会把自己比较的基准也同步拉高，永远测不出"放量"
+        assert_eq!(second.volume_avg, dec!(4));
+    }
+}
@@ -0,0 +1,13 @@
+//! 容错层：重试退避、断线重连、订单簿序列号缺口检测
+//!
+//! 三者通常一起使用：`reconnect` 模块负责在连接掉线后用 `retry` 的退避策略重新建连并
+//! 恢复订阅；重连期间可能漏掉的增量由 `sequence` 模块检测出来，驱动一次 `OrderBookManager`
+//! 全量重建，而不是让策略在一本已知有缺口的订单簿上继续做决策。
+
+pub mod reconnect;
+pub mod retry;
+pub mod sequence;
+
+pub use reconnect::ReconnectingStream;
+pub use retry::{retry as retry_with_backoff, RetryPolicy};
+pub use sequence::{resync, SequenceCheck, SequenceTracker};
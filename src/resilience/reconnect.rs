@@ -0,0 +1,81 @@
+//! 断线自动重连的 WebSocket 封装
+//!
+//! 包一层在 `WebSocketStream` 外面：连接掉线后，`reconnect` 会用 [`retry`] 按退避曲线
+//! 反复尝试重新建连，成功后自动把之前订阅过的市场频道重新订阅一遍，调用方不需要自己
+//! 记住订阅过什么。
+
+use super::retry::{retry, RetryPolicy};
+use crate::errors::Result;
+use crate::types::StreamMessage;
+use crate::WebSocketStream;
+
+/// 对 `WebSocketStream` 的断线重连封装
+pub struct ReconnectingStream {
+    url: String,
+    channels: Vec<String>,
+    retry_policy: RetryPolicy,
+    stream: Option<WebSocketStream>,
+}
+
+impl ReconnectingStream {
+    pub fn new(url: impl Into<String>, retry_policy: RetryPolicy) -> Self {
+        Self {
+            url: url.into(),
+            channels: Vec::new(),
+            retry_policy,
+            stream: None,
+        }
+    }
+
+    /// 建立初次连接
+    pub async fn connect(&mut self) -> Result<()> {
+        let url = self.url.clone();
+        let stream = retry(&self.retry_policy, || async {
+            Ok(WebSocketStream::new(&url))
+        })
+        .await?;
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    /// 订阅一组市场频道，并记住它们，以便断线重连后自动恢复
+    pub async fn subscribe_market_channel(&mut self, token_ids: Vec<String>) -> Result<()> {
+        for token_id in &token_ids {
+            if !self.channels.contains(token_id) {
+                self.channels.push(token_id.clone());
+            }
+        }
+        if let Some(stream) = &mut self.stream {
+            stream.subscribe_market_channel(token_ids).await?;
+        }
+        Ok(())
+    }
+
+    /// 连接掉线后调用：按退避曲线重试重新建连，成功后重新订阅之前的所有频道
+    pub async fn reconnect(&mut self) -> Result<()> {
+        self.stream = None;
+        let url = self.url.clone();
+        let channels = self.channels.clone();
+        let stream = retry(&self.retry_policy, || {
+            let channels = channels.clone();
+            async {
+                let mut stream = WebSocketStream::new(&url);
+                if !channels.is_empty() {
+                    stream.subscribe_market_channel(channels).await?;
+                }
+                Ok(stream)
+            }
+        })
+        .await?;
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    /// 读取下一条消息；返回 `None` 代表连接已经结束，调用方应当调用 `reconnect`
+    pub async fn next_message(&mut self) -> Option<Result<StreamMessage>> {
+        match &mut self.stream {
+            Some(stream) => stream.next().await,
+            None => None,
+        }
+    }
+}
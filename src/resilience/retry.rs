@@ -0,0 +1,133 @@
+//! 带退避的重试助手
+//!
+//! 抓取行情或提交订单时遇到超时、瞬时错误或连接中断，不应该直接向上冒泡失败——
+//! `retry` 会按 [`RetryPolicy`] 描述的退避曲线反复尝试，直到拿到有效结果或者用光重试次数。
+
+use crate::errors::Result;
+use std::future::Future;
+use std::time::Duration;
+use tracing::warn;
+
+/// 重试的退避策略：首次等待 `initial_backoff`，之后每次失败把等待时间乘以 `multiplier`，
+/// 直到触达 `max_backoff`；最多尝试 `max_attempts` 次（含第一次）。
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            initial_backoff,
+            max_backoff,
+            multiplier: 2.0,
+        }
+    }
+
+    fn backoff_for(&self, attempt: u32) -> Duration {
+        let scaled = self.initial_backoff.as_secs_f64() * self.multiplier.powi(attempt as i32 - 1);
+        Duration::from_secs_f64(scaled).min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(5, Duration::from_millis(200), Duration::from_secs(10))
+    }
+}
+
+/// 反复调用 `op`，直到成功或者用光 `policy.max_attempts` 次尝试
+pub async fn retry<F, Fut, T>(policy: &RetryPolicy, mut op: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < policy.max_attempts => {
+                warn!(
+                    "操作失败 (第 {}/{} 次尝试): {}，{:?} 后重试",
+                    attempt,
+                    policy.max_attempts,
+                    err,
+                    policy.backoff_for(attempt)
+                );
+                tokio::time::sleep(policy.backoff_for(attempt)).await;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn backoff_for_doubles_each_attempt_until_capped() {
+        let policy = RetryPolicy::new(10, Duration::from_millis(100), Duration::from_secs(1));
+
+        assert_eq!(policy.backoff_for(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff_for(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff_for(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff_for(4), Duration::from_millis(800));
+        // 第 5 次按曲线应该是 1600ms，但已经超过 max_backoff，封顶在 1s
+        assert_eq!(policy.backoff_for(5), Duration::from_secs(1));
+        assert_eq!(policy.backoff_for(10), Duration::from_secs(1));
+    }
+
+    // 用一次真实会失败的 `std::fs::read_to_string` 来制造 `Result::Err`，这样不需要
+    // 对 `crate::errors::Error` 的具体变体做任何假设，只依赖它已知实现了 `From<io::Error>`
+    // （`StrategyParamManager::load` 里的 `?` 已经证明了这一点）
+    fn read_missing_file() -> Result<()> {
+        std::fs::read_to_string("/nonexistent/polyfill_rs_retry_test_path").map(|_| ())?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn retry_gives_up_after_max_attempts() {
+        let policy = RetryPolicy::new(3, Duration::from_millis(1), Duration::from_millis(5));
+        let attempts = Cell::new(0);
+
+        let result: Result<()> = retry(&policy, || {
+            attempts.set(attempts.get() + 1);
+            async { read_missing_file() }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn retry_returns_as_soon_as_op_succeeds() {
+        let policy = RetryPolicy::default();
+        let attempts = Cell::new(0);
+
+        let result = retry(&policy, || {
+            attempts.set(attempts.get() + 1);
+            // 不能用 `async move`：`attempts` 不是 `Copy`，`move` 会把它整个搬进每次
+            // 调用产生的新 future 里，这个闭包是 `FnMut` 会被反复调用，之后还要再读
+            // `attempts.get()`，只能借用它
+            async {
+                if attempts.get() < 2 {
+                    read_missing_file()?;
+                }
+                Ok(42)
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(result, 42);
+        assert_eq!(attempts.get(), 2);
+    }
+}
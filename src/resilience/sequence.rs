@@ -0,0 +1,168 @@
+//! 订单簿序列号连续性检查与重新同步
+//!
+//! `OrderDelta.sequence` 应该逐条递增；重连之后，如果下一条增量的序列号比上一次见到的
+//! 大了不止 1，说明中间丢了数据，继续往本地订单簿上套用增量只会让它越来越不准，
+//! 此时应该触发一次通过 `OrderBookManager` 的全量重建，而不是假装什么都没发生。
+
+use crate::book::OrderBookManager;
+use crate::errors::Result;
+use crate::types::OrderDelta;
+use std::collections::HashMap;
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceCheck {
+    /// 序列号连续（或者是该代币收到的第一条增量）
+    Ok,
+    /// 序列号跳跃，中间缺失了 `[expected, got)` 这一段数据
+    Gap { expected: u64, got: u64 },
+}
+
+/// 按 token_id 跟踪每个订单簿上一次见到的序列号
+#[derive(Debug, Default)]
+pub struct SequenceTracker {
+    last_sequence: HashMap<String, u64>,
+}
+
+impl SequenceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 检查一条新增量相对上一次见到的序列号是否连续，并记下这次的序列号
+    ///
+    /// 重复或者乱序到达（序列号不大于已记录的水位线）的增量不会推进水位线——否则水位线
+    /// 被往回拨一次之后，下一条真正按序到达的增量会被误判成缺口，触发不必要的重建。
+    pub fn check(&mut self, delta: &OrderDelta) -> SequenceCheck {
+        let previous = self.last_sequence.get(&delta.token_id).copied();
+        let should_advance = match previous {
+            Some(prev) => delta.sequence > prev,
+            None => true,
+        };
+        if should_advance {
+            self.last_sequence
+                .insert(delta.token_id.clone(), delta.sequence);
+        }
+        match previous {
+            Some(prev) if delta.sequence > prev + 1 => SequenceCheck::Gap {
+                expected: prev + 1,
+                got: delta.sequence,
+            },
+            _ => SequenceCheck::Ok,
+        }
+    }
+
+    /// 重连之后调用：丢弃已记录的序列号，下一条增量不会被当成 gap
+    pub fn reset(&mut self, token_id: &str) {
+        self.last_sequence.remove(token_id);
+    }
+
+    /// 导出当前各 token 的最后处理序列号，用于持久化检查点
+    pub fn snapshot(&self) -> HashMap<String, u64> {
+        self.last_sequence.clone()
+    }
+
+    /// 从持久化检查点恢复各 token 的最后处理序列号（重启后调用）
+    pub fn restore(&mut self, snapshot: HashMap<String, u64>) {
+        self.last_sequence = snapshot;
+    }
+}
+
+/// 触发一次全量重建：清空本地订单簿状态，重新当作一张空簿等待后续增量重新堆叠起来。
+///
+/// 这只是让本地状态不再基于已知有缺口的数据做决策；真正补全缺失的深度仍然需要对应的
+/// REST/snapshot 接口重新拉一次全量快照。
+pub fn resync(book_manager: &mut OrderBookManager, token_id: &str) -> Result<()> {
+    warn!("检测到序列号缺口，重建 {} 的本地订单簿", token_id);
+    book_manager.reset_book(token_id)?;
+    book_manager.get_or_create_book(token_id)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn delta(token_id: &str, sequence: u64) -> OrderDelta {
+        OrderDelta {
+            token_id: token_id.to_string(),
+            timestamp: chrono::Utc::now(),
+            side: crate::types::Side::BUY,
+            price: dec!(0.5),
+            size: dec!(10),
+            sequence,
+        }
+    }
+
+    #[test]
+    fn first_delta_for_a_token_is_always_ok() {
+        let mut tracker = SequenceTracker::new();
+        assert_eq!(tracker.check(&delta("a", 42)), SequenceCheck::Ok);
+    }
+
+    #[test]
+    fn consecutive_sequences_are_ok() {
+        let mut tracker = SequenceTracker::new();
+        tracker.check(&delta("a", 1));
+        assert_eq!(tracker.check(&delta("a", 2)), SequenceCheck::Ok);
+        assert_eq!(tracker.check(&delta("a", 3)), SequenceCheck::Ok);
+    }
+
+    #[test]
+    fn a_skipped_sequence_is_reported_as_a_gap() {
+        let mut tracker = SequenceTracker::new();
+        tracker.check(&delta("a", 1));
+        assert_eq!(
+            tracker.check(&delta("a", 5)),
+            SequenceCheck::Gap {
+                expected: 2,
+                got: 5
+            }
+        );
+    }
+
+    #[test]
+    fn tokens_are_tracked_independently() {
+        let mut tracker = SequenceTracker::new();
+        tracker.check(&delta("a", 10));
+        // token "b" 还没见过任何序列号，不应该被 "a" 的进度影响
+        assert_eq!(tracker.check(&delta("b", 1)), SequenceCheck::Ok);
+    }
+
+    #[test]
+    fn a_duplicate_or_out_of_order_delta_does_not_rewind_the_watermark() {
+        let mut tracker = SequenceTracker::new();
+        tracker.check(&delta("a", 10));
+        // 重复/乱序的旧序列号不应该把水位线拨回去，否则下一条正常按序到达的增量
+        // (11) 会被误判成从 6 开始缺了一大段
+        assert_eq!(tracker.check(&delta("a", 5)), SequenceCheck::Ok);
+        assert_eq!(tracker.check(&delta("a", 11)), SequenceCheck::Ok);
+    }
+
+    #[test]
+    fn reset_forgets_the_last_sequence_for_a_token() {
+        let mut tracker = SequenceTracker::new();
+        tracker.check(&delta("a", 1));
+        tracker.reset("a");
+        // 重连之后序列号可能从任何地方续上，不应该被误判成 gap
+        assert_eq!(tracker.check(&delta("a", 100)), SequenceCheck::Ok);
+    }
+
+    #[test]
+    fn snapshot_and_restore_round_trip_the_tracked_state() {
+        let mut tracker = SequenceTracker::new();
+        tracker.check(&delta("a", 7));
+        let snapshot = tracker.snapshot();
+
+        let mut restored = SequenceTracker::new();
+        restored.restore(snapshot);
+        assert_eq!(
+            restored.check(&delta("a", 9)),
+            SequenceCheck::Gap {
+                expected: 8,
+                got: 9
+            }
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! `StrategyContext` 的本地模拟实现
+//!
+//! 在回测、纸上交易或者没有真实交易所连接的示例里，策略仍然需要一个 `StrategyContext`
+//! 来下单、撤单、查仓位。`SimContext` 在本地维护一份订单簿镜像和一个 `FillEngine`，
+//! 把 `place_order` 模拟成针对当前盘口的市价单成交，并据此记账持仓——这正是
+//! `examples/snipe.rs` 里 `execute_snipe_order` 原本手写的那部分逻辑。
+
+use crate::book::{OrderBook, OrderBookManager};
+use crate::errors::Result;
+use crate::fill::{FillEngine, FillResult, FillStatus};
+use crate::strategy::StrategyContext;
+use crate::types::{MarketOrderRequest, OrderDelta, Side};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// 本地模拟的执行上下文：维护一份订单簿镜像、一个 `FillEngine`，以及持仓/挂单/定时器的
+/// 本地账本
+pub struct SimContext {
+    book_manager: OrderBookManager,
+    fill_engine: FillEngine,
+    positions: HashMap<String, Decimal>,
+    pending_orders: HashSet<String>,
+    /// 已注册但尚未到期的定时器：(到期时刻, 定时器 ID)
+    pending_timers: Vec<(Instant, String)>,
+}
+
+impl SimContext {
+    pub fn new(book_depth: usize, fill_engine: FillEngine) -> Self {
+        Self {
+            book_manager: OrderBookManager::new(book_depth),
+            fill_engine,
+            positions: HashMap::new(),
+            pending_orders: HashSet::new(),
+            pending_timers: Vec::new(),
+        }
+    }
+
+    /// 把行情增量应用到本地的订单簿镜像上，供 `place_order` 撮合时使用
+    pub fn apply_delta(&mut self, delta: OrderDelta) -> Result<()> {
+        self.book_manager.get_or_create_book(&delta.token_id)?;
+        self.book_manager.apply_delta(delta)
+    }
+
+    /// 当前仍未成交、可供取消的挂单 ID 列表
+    pub fn pending_order_ids(&self) -> Vec<String> {
+        self.pending_orders.iter().cloned().collect()
+    }
+
+    /// 取出并移除当前已经到期的定时器 ID，调用方据此触发策略的 `on_timer`
+    pub fn take_due_timers(&mut self) -> Vec<String> {
+        let now = Instant::now();
+        let mut due = Vec::new();
+        self.pending_timers.retain(|(deadline, id)| {
+            if *deadline <= now {
+                due.push(id.clone());
+                false
+            } else {
+                true
+            }
+        });
+        due
+    }
+
+    fn snapshot_book(&self, token_id: &str) -> Result<OrderBook> {
+        let book = self.book_manager.get_book(token_id)?;
+        let mut book_impl = OrderBook::new(token_id.to_string(), 100);
+        for (sequence, level) in book.bids.iter().enumerate() {
+            book_impl.apply_delta(OrderDelta {
+                token_id: token_id.to_string(),
+                timestamp: chrono::Utc::now(),
+                side: Side::BUY,
+                price: level.price,
+                size: level.size,
+                sequence: sequence as u64,
+            })?;
+        }
+        for (sequence, level) in book.asks.iter().enumerate() {
+            book_impl.apply_delta(OrderDelta {
+                token_id: token_id.to_string(),
+                timestamp: chrono::Utc::now(),
+                side: Side::SELL,
+                price: level.price,
+                size: level.size,
+                sequence: sequence as u64,
+            })?;
+        }
+        Ok(book_impl)
+    }
+}
+
+impl StrategyContext for SimContext {
+    fn place_order(&mut self, request: MarketOrderRequest) -> Result<FillResult> {
+        let book = self.snapshot_book(&request.token_id)?;
+        let result = self.fill_engine.execute_market_order(&request, &book)?;
+
+        let signed_size = match request.side {
+            Side::BUY => result.total_size,
+            Side::SELL => -result.total_size,
+        };
+        *self
+            .positions
+            .entry(request.token_id.clone())
+            .or_insert(dec!(0)) += signed_size;
+
+        if let Some(client_id) = &request.client_id {
+            if result.status != FillStatus::Filled {
+                self.pending_orders.insert(client_id.clone());
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn cancel_order(&mut self, order_id: &str) -> Result<()> {
+        self.pending_orders.remove(order_id);
+        Ok(())
+    }
+
+    fn subscribe(&mut self, _token_ids: Vec<String>) -> Result<()> {
+        // 本地模拟不需要真正订阅频道，行情直接通过 apply_delta 喂入
+        Ok(())
+    }
+
+    fn query_position(&self, token_id: &str) -> Decimal {
+        self.positions.get(token_id).copied().unwrap_or(dec!(0))
+    }
+
+    fn add_timer(&mut self, after: Duration, timer_id: String) {
+        self.pending_timers.push((Instant::now() + after, timer_id));
+    }
+
+    fn reconnect(&mut self) -> Result<()> {
+        // 本地模拟没有真实连接可以断开，重连在这里是空操作
+        Ok(())
+    }
+}
@@ -0,0 +1,525 @@
+//! 狙击（"snipe"）策略：`Strategy` 的一个具体实现
+//!
+//! 这原本是 `examples/snipe.rs` 里的一个示例专用结构体，现在提升到库里，这样
+//! `examples/backtest_snipe.rs` 之类需要"跑真实策略代码"的场景可以直接复用同一个
+//! `SnipeStrategy`，而不是在示例里重新攒一份简化版逻辑——回测和实盘才能真正共享同一条
+//! 代码路径。`examples/snipe.rs` 现在只是这个策略的一个可运行的壳（生成模拟行情、驱动
+//! `strategy::dispatch`、打印统计）。
+
+use crate::book::OrderBookManager;
+use crate::errors::Result;
+use crate::fill::FillStatus;
+use crate::indicators::{kdj_crossed_up, IndicatorSuite};
+use crate::resilience::{self, SequenceCheck, SequenceTracker};
+use crate::stats::SnipeStats;
+use crate::store::PersistentStore;
+use crate::strategy::{Strategy, StrategyContext, StrategyParamManager};
+use crate::types::{FillEvent, MarketOrderRequest, OrderDelta, Side};
+use crate::utils::time;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tracing::{info, warn};
+
+/// 狙击交易策略
+///
+/// 这个结构体包含了执行 "snipe" 策略所需的所有状态和配置。
+/// 它监控订单簿的变化，检测交易机会，并通过 `StrategyContext` 执行快速订单。
+pub struct SnipeStrategy {
+    /// 目标代币 ID（要交易的市场代币）
+    token_id: String,
+
+    /// PnL 计算的权益基线：第一次启动时记录一次，重启后从检查点恢复，不会被重置
+    initial_equity: Decimal,
+
+    /// 运行时可调参数（max_spread_pct / min_order_size / max_order_size / stale_threshold）
+    params: StrategyParamManager,
+
+    /// 上一次记录的最佳买价（best bid）
+    last_best_bid: Option<Decimal>,
+
+    /// 上一次记录的最佳卖价（best ask）
+    last_best_ask: Option<Decimal>,
+
+    /// 最后一次更新的时间戳（秒）
+    last_update: u64,
+
+    /// 数据过时时是否已暂停交易
+    paused: bool,
+
+    /// 本策略还未成交、可供取消的挂单 ID（用于过时报价时的 cancel-all）
+    open_order_ids: Vec<String>,
+
+    /// 订单簿管理器：维护本地订单簿状态
+    /// 用于快速查询买卖价格和流动性
+    book_manager: OrderBookManager,
+
+    /// 把成交 tick 聚合成 K 线，并增量计算 KDJ/均线/量均值，供价差逻辑之外叠加技术面过滤
+    indicators: IndicatorSuite,
+
+    /// 跟踪各代币的订单簿序列号，检测重连后是否漏收了增量
+    sequence_tracker: SequenceTracker,
+
+    /// 统计数据：记录策略的运行统计信息
+    stats: SnipeStats,
+
+    /// 当前持仓（正数为多头，负数为空头），用加权平均成本法和 `avg_entry_price` 一起
+    /// 跟踪已实现盈亏
+    position: Decimal,
+
+    /// 当前持仓的加权平均开仓价；没有持仓时为 0
+    avg_entry_price: Decimal,
+
+    /// 检查点存储：重启后恢复统计数据、挂单 ID、序列号进度，而不是从零开始
+    store: PersistentStore,
+}
+
+impl SnipeStrategy {
+    /// 创建一个新的狙击策略实例
+    ///
+    /// 如果 `store` 里已经有上一次运行留下的检查点（挂单 ID、统计数据、订单簿序列号
+    /// 进度、持仓成本），会从中恢复，而不是当成全新的一次运行——bot 可能因为部署、崩溃
+    /// 或者手动重连而重启，找回这些状态才能接着算 PnL、避免重复下单。
+    ///
+    /// # 参数
+    /// - `token_id`: 要交易的市场代币 ID
+    /// - `params`: 运行时可调参数管理器（max_spread_pct、min/max_order_size、stale_threshold）
+    /// - `store`: 检查点存储
+    ///
+    /// # 返回
+    /// 配置好的策略实例，包含订单簿管理器
+    pub fn new(token_id: String, params: StrategyParamManager, store: PersistentStore) -> Result<Self> {
+        // 初始权益只在第一次启动时记录一次；后面每次重启都从检查点里读回同一个基线，
+        // 这样 PnL 统计不会因为重启而被错误地清零重算
+        let initial_equity = match store.load("initial_equity")? {
+            Some(equity) => equity,
+            None => {
+                let equity = dec!(0);
+                store.save("initial_equity", &equity)?;
+                equity
+            }
+        };
+        let open_order_ids = store.load("open_order_ids")?.unwrap_or_default();
+        let stats = store.load("stats")?.unwrap_or_default();
+        let last_sequence = store.load("last_sequence")?.unwrap_or_default();
+        let position = store.load("position")?.unwrap_or_default();
+        let avg_entry_price = store.load("avg_entry_price")?.unwrap_or_default();
+
+        let mut sequence_tracker = SequenceTracker::new();
+        sequence_tracker.restore(last_sequence);
+
+        Ok(Self {
+            token_id,
+            initial_equity,
+            params,
+            last_best_bid: None,
+            last_best_ask: None,
+            last_update: 0,
+            paused: false,
+            open_order_ids,
+            // 创建订单簿管理器，保留前 100 个价格层级
+            // 更多的层级可以捕获更多流动性，但占用更多内存
+            book_manager: OrderBookManager::new(100),
+            // 1 分钟 K 线，保留最近 200 根，KDJ/均线窗口取 14 根
+            indicators: IndicatorSuite::new(60, 200, 14),
+            sequence_tracker,
+            stats,
+            position,
+            avg_entry_price,
+            store,
+        })
+    }
+
+    /// 把关键状态写入检查点存储，供重启后恢复
+    ///
+    /// 合并成一次 `save_batch` 而不是五次独立的 `save`：每次 `save` 都是一轮
+    /// 读-改-写，五次独立调用中间有四个短暂的、只含部分字段的磁盘状态，这里没有
+    /// 必要付这个代价，一次批量写入就够了。
+    fn checkpoint(&self) -> Result<()> {
+        self.store.save_batch(vec![
+            ("open_order_ids", serde_json::to_value(&self.open_order_ids)?),
+            ("stats", serde_json::to_value(&self.stats)?),
+            (
+                "last_sequence",
+                serde_json::to_value(self.sequence_tracker.snapshot())?,
+            ),
+            ("position", serde_json::to_value(self.position)?),
+            ("avg_entry_price", serde_json::to_value(self.avg_entry_price)?),
+        ])
+    }
+
+    /// 处理订单簿更新
+    ///
+    /// 当收到新的订单簿更新时：
+    /// 1. 确保该代币的订单簿存在
+    /// 2. 将更新应用到本地订单簿
+    /// 3. 更新最佳买卖价格
+    /// 4. 检查是否有交易机会
+    ///
+    /// # 参数
+    /// - `delta`: 订单簿增量更新（包含价格、数量、方向等信息）
+    fn process_book_update(&mut self, ctx: &mut dyn StrategyContext, delta: OrderDelta) -> Result<()> {
+        // 确保订单簿存在（如果不存在则创建）
+        self.book_manager.get_or_create_book(&self.token_id)?;
+
+        // 序列号跳跃说明中间丢了增量（通常是重连期间漏收的），继续套用只会让本地簿
+        // 越来越不准，此时触发一次全量重建
+        if let SequenceCheck::Gap { expected, got } = self.sequence_tracker.check(&delta) {
+            warn!(
+                "{} 的订单簿序列号出现缺口: 期望 {}, 实际 {}",
+                self.token_id, expected, got
+            );
+            resilience::resync(&mut self.book_manager, &self.token_id)?;
+        }
+
+        // 将增量更新应用到本地订单簿
+        // 这使用了 polyfill-rs 的高性能固定点算法，速度非常快
+        self.book_manager.apply_delta(delta)?;
+
+        // 获取更新后的订单簿状态
+        let book = self.book_manager.get_book(&self.token_id)?;
+
+        // 更新最佳买价（订单簿买方的最高价格，即 bids 的第一项）
+        if let Some(best_bid) = book.bids.first() {
+            self.last_best_bid = Some(best_bid.price);
+        }
+        // 更新最佳卖价（订单簿卖方的最低价格，即 asks 的第一项）
+        if let Some(best_ask) = book.asks.first() {
+            self.last_best_ask = Some(best_ask.price);
+        }
+
+        // 更新最后更新时间戳
+        self.last_update = time::now_secs();
+
+        // 检查是否有交易机会（价差是否满足条件）
+        self.check_opportunities(ctx)?;
+
+        Ok(())
+    }
+
+    /// 处理成交记录更新
+    ///
+    /// 当市场中有订单成交时，更新统计信息。
+    ///
+    /// # 参数
+    /// - `fill`: 成交事件（包含价格、数量、方向等信息）
+    fn process_trade(&mut self, fill: FillEvent) -> Result<()> {
+        info!(
+            "成交: {} {} @ {} (数量: {})",
+            fill.side.as_str(), // "BUY" 或 "SELL"
+            fill.token_id,      // 代币 ID
+            fill.price,         // 成交价格
+            fill.size           // 成交数量
+        );
+
+        // 更新总成交量统计
+        self.stats.total_volume += fill.size;
+
+        // 把这笔成交喂给指标套件；只有触发了一根 K 线收盘才会拿到新的快照
+        let previous_kdj = self.indicators.last_kdj();
+        if let Some(snapshot) = self
+            .indicators
+            .on_trade(fill.price, fill.size, time::now_secs())
+        {
+            let volume_surge = snapshot.candle.volume > snapshot.volume_avg * dec!(1.5);
+            let golden_cross = previous_kdj
+                .map(|prev| kdj_crossed_up(prev, snapshot.kdj))
+                .unwrap_or(false);
+
+            if volume_surge && golden_cross {
+                info!(
+                    "技术面信号: 放量 {} (均量 {}) 且 K 上穿 D (K={}, D={})",
+                    snapshot.candle.volume,
+                    snapshot.volume_avg,
+                    snapshot.kdj.k,
+                    snapshot.kdj.d
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 检查交易机会
+    ///
+    /// 核心逻辑：计算当前买卖价差，如果价差足够小（满足条件），
+    /// 就执行交易来捕捉这个价差。
+    ///
+    /// # 策略逻辑
+    /// - 价差 = (最佳卖价 - 最佳买价) / 最佳买价 * 100%
+    /// - 如果价差 <= max_spread_pct，说明市场流动性好，有机会快速成交
+    /// - 此时可以买入然后立即卖出（或反之），赚取价差
+    fn check_opportunities(&mut self, ctx: &mut dyn StrategyContext) -> Result<()> {
+        if self.paused {
+            return Ok(()); // 数据过时触发的暂停期间不交易
+        }
+
+        // 获取最佳买卖价格
+        let (bid, ask) = match (self.last_best_bid, self.last_best_ask) {
+            (Some(bid), Some(ask)) => (bid, ask),
+            _ => return Ok(()), // 没有流动性，无法交易
+        };
+
+        let max_spread_pct = self.params.params().max_spread_pct;
+
+        // 计算价差百分比
+        // 价差 = (卖价 - 买价) / 买价 * 100%
+        // 例如：买价 0.50，卖价 0.51，价差 = (0.51-0.50)/0.50*100 = 2%
+        let spread_pct = match (bid, ask) {
+            (bid, ask) if bid > dec!(0) && ask > bid => (ask - bid) / bid * dec!(100),
+            _ => return Ok(()), // 价格无效或市场交叉（买价 > 卖价，异常情况）
+        };
+
+        // 检查价差是否满足我们的交易条件
+        // 价差越小，说明市场越紧密，我们越容易捕捉到机会
+        if spread_pct <= max_spread_pct {
+            // 发现交易机会！
+            self.stats.opportunities_detected += 1;
+
+            info!(
+                "发现交易机会: 价差 {}% (目标: {}%)",
+                spread_pct, max_spread_pct
+            );
+
+            // 执行狙击订单（快速买入或卖出）
+            self.execute_snipe_order(ctx, bid, ask)?;
+        }
+
+        Ok(())
+    }
+
+    /// 执行狙击订单
+    ///
+    /// 这是策略的执行部分，当发现交易机会时：
+    /// 1. 确定订单大小（在最小和最大值之间）
+    /// 2. 确定交易方向（买入或卖出）
+    /// 3. 创建市价单请求
+    /// 4. 通过 `StrategyContext::place_order` 执行订单
+    /// 5. 按加权平均成本法把这笔成交计入持仓和已实现盈亏
+    ///
+    /// # 参数
+    /// - `bid`: 当前最佳买价
+    /// - `ask`: 当前最佳卖价
+    fn execute_snipe_order(
+        &mut self,
+        ctx: &mut dyn StrategyContext,
+        bid: Decimal,
+        ask: Decimal,
+    ) -> Result<()> {
+        let (min_order_size, max_order_size) = {
+            let params = self.params.params();
+            (params.min_order_size, params.max_order_size)
+        };
+
+        // 计算订单大小（在最小和最大值之间随机选择）
+        let random_factor = Decimal::from(rand::random::<u64>() % 100) / Decimal::from(100);
+        let size = min_order_size + (max_order_size - min_order_size) * random_factor;
+
+        // 根据市场条件决定交易方向
+        let side = if bid > ask {
+            // 市场交叉（买价 > 卖价，异常情况），选择卖出
+            Side::SELL
+        } else {
+            // 正常市场，选择买入（假设我们会立即卖出套利）
+            Side::BUY
+        };
+
+        let client_id = format!("snipe_{}", time::now_millis());
+
+        // 创建市价单请求
+        // 市价单会以当前最优价格立即执行，速度快但可能有滑点
+        let request = MarketOrderRequest {
+            token_id: self.token_id.clone(),
+            side,                                // 买入或卖出
+            amount: size,                        // 订单金额
+            slippage_tolerance: Some(dec!(1.0)), // 1% 滑点容忍度（允许实际成交价偏离预期 1%）
+            client_id: Some(client_id.clone()),  // 客户端订单 ID（用于追踪）
+        };
+
+        let start_time = std::time::Instant::now();
+        let result = ctx.place_order(request)?;
+        let fill_time = start_time.elapsed().as_millis() as f64; // 记录执行耗时
+
+        // 更新统计信息
+        self.stats.orders_placed += 1; // 订单已提交
+        if result.status == FillStatus::Filled {
+            self.stats.orders_filled += 1; // 订单已完全成交
+        } else {
+            // 没有立即完全成交，记录下来以便过时报价时取消
+            self.open_order_ids.push(client_id);
+        }
+
+        // 把这笔实际成交按加权平均成本法计入持仓，反向成交部分按成本价结算实现盈亏
+        self.apply_fill(side, result.total_size, result.average_price);
+
+        // 更新平均成交时间（使用移动平均）
+        // 公式：新平均值 = (旧平均值 * (n-1) + 新值) / n
+        // 只有这一单完全成交时才算一个新样本，n 为包含这一单在内的已成交订单数
+        if result.status == FillStatus::Filled {
+            let total_time =
+                self.stats.avg_fill_time_ms * (self.stats.orders_filled - 1) as f64 + fill_time;
+            self.stats.avg_fill_time_ms = total_time / self.stats.orders_filled as f64;
+        }
+
+        info!(
+            "狙击订单已执行: {} {} @ {} (成交时间: {}ms)",
+            result.total_size,    // 成交总数量
+            side.as_str(),        // 交易方向
+            result.average_price, // 平均成交价格
+            fill_time              // 执行耗时（毫秒）
+        );
+
+        Ok(())
+    }
+
+    /// 把一笔实际成交按加权平均成本法计入持仓：
+    /// - 同方向加仓：按数量加权平均更新开仓成本，不产生已实现盈亏
+    /// - 反方向成交：按 `(平仓价 - 开仓成本)` 结算被平掉那部分的已实现盈亏，
+    ///   超出原持仓的部分按新方向、以这次的成交价重新开仓
+    fn apply_fill(&mut self, side: Side, filled: Decimal, fill_price: Decimal) {
+        if filled <= dec!(0) {
+            return;
+        }
+
+        let signed_fill = match side {
+            Side::BUY => filled,
+            Side::SELL => -filled,
+        };
+
+        let same_direction = self.position == dec!(0)
+            || (self.position > dec!(0) && signed_fill > dec!(0))
+            || (self.position < dec!(0) && signed_fill < dec!(0));
+
+        if same_direction {
+            let new_position = self.position + signed_fill;
+            self.avg_entry_price = (self.avg_entry_price * self.position.abs()
+                + fill_price * filled)
+                / new_position.abs();
+            self.position = new_position;
+            return;
+        }
+
+        let position_was_long = self.position > dec!(0);
+        let closing = filled.min(self.position.abs());
+        let realized = if position_was_long {
+            (fill_price - self.avg_entry_price) * closing
+        } else {
+            (self.avg_entry_price - fill_price) * closing
+        };
+        self.stats.total_pnl += realized;
+
+        if position_was_long {
+            self.position -= closing;
+        } else {
+            self.position += closing;
+        }
+
+        let leftover = filled - closing;
+        if leftover > dec!(0) {
+            // 平仓数量超过了原持仓，剩下的部分按新方向、以这次成交价重新开仓
+            self.avg_entry_price = fill_price;
+            self.position = match side {
+                Side::BUY => leftover,
+                Side::SELL => -leftover,
+            };
+        } else if self.position == dec!(0) {
+            self.avg_entry_price = dec!(0);
+        }
+    }
+
+    /// 检查过时的报价数据
+    ///
+    /// 如果订单簿数据太久没有更新，可能意味着：
+    /// - WebSocket 连接断开
+    /// - 市场流动性很差
+    /// - 数据源有问题
+    ///
+    /// 在这种情况下继续交易是危险的，因为订单簿可能已经过时。
+    ///
+    /// # 处理策略
+    /// 当检测到数据过时时：
+    /// - 取消所有待处理订单（避免基于错误数据交易）
+    /// - 暂停交易
+    /// - 把最大订单大小减半，降低恢复交易后的风险敞口
+    /// - 尝试重新连接数据流；重连期间可能漏收的增量由 `process_book_update` 里的
+    ///   序列号检查发现并触发重建
+    fn check_stale_quotes(&mut self, ctx: &mut dyn StrategyContext) -> Result<()> {
+        // 每次心跳都先看一眼参数文件有没有被手动改过，改过就重新读入，这样编辑
+        // snipe_params.json 真的会在下一次心跳生效，而不是被后面的 `update()` 悄悄覆盖
+        if self.params.reload_if_changed()? {
+            info!("检测到参数文件变化，已重新加载 StrategyParams");
+        }
+
+        let now = time::now_secs();
+        let age = now.saturating_sub(self.last_update); // 数据年龄（秒）
+
+        // 如果数据超过阈值未更新，认为数据已过时
+        if age > self.params.params().stale_threshold {
+            warn!(
+                "检测到过时报价: 数据已 {} 秒未更新 (阈值: {} 秒)",
+                age,
+                self.params.params().stale_threshold
+            );
+
+            // 取消所有待处理订单：避免基于过时数据成交
+            for order_id in self.open_order_ids.drain(..) {
+                ctx.cancel_order(&order_id)?;
+            }
+
+            // 暂停交易，直到收到新鲜的行情数据
+            self.paused = true;
+
+            // 减少仓位大小：把最大订单大小减半
+            self.params.update(|p| p.max_order_size /= dec!(2))?;
+
+            // 尝试重新连接数据流
+            ctx.reconnect()?;
+            // 重连后不知道新连接会从哪个序列号续上，清掉记录避免把它误判成缺口
+            self.sequence_tracker.reset(&self.token_id);
+        } else {
+            // 数据恢复新鲜后自动恢复交易
+            self.paused = false;
+        }
+
+        // 每次心跳都落一次检查点，这样崩溃或重启不会丢掉挂单、统计和序列号进度
+        self.checkpoint()?;
+
+        Ok(())
+    }
+
+    /// 获取当前统计数据
+    ///
+    /// 返回策略运行过程中的所有统计信息，用于监控和评估策略表现。
+    pub fn get_stats(&self) -> &SnipeStats {
+        &self.stats
+    }
+
+    /// 当前权益 = 启动时记录的基线 + 累计已实现盈亏
+    pub fn current_equity(&self) -> Decimal {
+        self.initial_equity + self.stats.total_pnl
+    }
+}
+
+impl Strategy for SnipeStrategy {
+    fn on_book_update(&mut self, ctx: &mut dyn StrategyContext, delta: OrderDelta) -> Result<()> {
+        if delta.token_id == self.token_id {
+            self.process_book_update(ctx, delta)?;
+        }
+        Ok(())
+    }
+
+    fn on_trade(&mut self, _ctx: &mut dyn StrategyContext, fill: FillEvent) -> Result<()> {
+        if fill.token_id == self.token_id {
+            self.process_trade(fill)?;
+        }
+        Ok(())
+    }
+
+    fn on_heartbeat(&mut self, ctx: &mut dyn StrategyContext, _timestamp: u64) -> Result<()> {
+        self.check_stale_quotes(ctx)
+    }
+
+    fn on_timer(&mut self, _ctx: &mut dyn StrategyContext, timer_id: &str) -> Result<()> {
+        info!("定时器触发: {}", timer_id);
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+//! 策略运行统计数据
+//!
+//! 这些统计字段原先是 `snipe` 示例里的私有结构体，现在提升到库里，这样
+//! `backtest` 和其它策略可以复用同一套统计口径，而不是各自重新定义一遍。
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+/// 策略的统计数据
+///
+/// 记录策略运行过程中的关键指标，用于评估策略表现。可序列化，这样
+/// `store::PersistentStore` 能把它当作一个检查点在重启之间保存/恢复。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnipeStats {
+    /// 检测到的交易机会数量（价差满足条件的情况）
+    pub opportunities_detected: u64,
+
+    /// 已提交的订单数量
+    pub orders_placed: u64,
+
+    /// 已成交的订单数量
+    pub orders_filled: u64,
+
+    /// 总交易量（累计成交的代币数量）
+    pub total_volume: Decimal,
+
+    /// 总盈亏（Profit & Loss）
+    pub total_pnl: Decimal,
+
+    /// 平均成交时间（毫秒）
+    /// 衡量订单执行速度，越小越好
+    pub avg_fill_time_ms: f64,
+}
+
+impl Default for SnipeStats {
+    fn default() -> Self {
+        Self {
+            opportunities_detected: 0,
+            orders_placed: 0,
+            orders_filled: 0,
+            total_volume: dec!(0),
+            total_pnl: dec!(0),
+            avg_fill_time_ms: 0.0,
+        }
+    }
+}
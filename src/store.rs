@@ -0,0 +1,152 @@
+//! 持久化的键值存储
+//!
+//! bot 会因为各种原因重启：部署、崩溃、手动重连……重启后策略需要找回关键状态——
+//! 用于 PnL 基线的初始权益、还没成交的挂单 ID、运行中的 `SnipeStats`、最后处理到的
+//! 序列号——而不是当成全新的一次运行。`PersistentStore` 把这些检查点存成磁盘上的一个
+//! JSON 文件，每个键值对独立读写。
+//!
+//! 写入按"写临时文件再 rename"的方式落盘：`rename` 在同一个文件系统上是原子的，
+//! 这样即便进程在写入过程中崩溃，`path` 本身要么是上一次完整的内容，要么是这一次
+//! 完整的内容，不会读到半份 JSON。一次检查点通常要更新好几个键，[`save_batch`]
+//! 把它们合并成一次读-改-写，避免 `save` 多次调用时中间状态互相覆盖、也减少磁盘 I/O。
+
+use crate::errors::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// 一个以 JSON 文件为后端的简单键值存储，用于跨重启保存策略的检查点状态
+pub struct PersistentStore {
+    path: PathBuf,
+}
+
+impl PersistentStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// 保存一个检查点；已存在的同名键会被覆盖
+    pub fn save<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        self.save_batch(vec![(key, serde_json::to_value(value)?)])
+    }
+
+    /// 一次性保存多个检查点，只做一次读-改-写和一次原子落盘，而不是每个键各一次
+    pub fn save_batch(&self, items: Vec<(&str, serde_json::Value)>) -> Result<()> {
+        let mut entries = self.load_all()?;
+        for (key, value) in items {
+            entries.insert(key.to_string(), value);
+        }
+        self.write_entries(&entries)
+    }
+
+    /// 读取一个检查点；键不存在时返回 `None`，调用方应当回退到默认初始状态
+    pub fn load<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let entries = self.load_all()?;
+        match entries.get(key) {
+            Some(value) => Ok(Some(serde_json::from_value(value.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    fn load_all(&self) -> Result<HashMap<String, serde_json::Value>> {
+        if !Path::new(&self.path).exists() {
+            return Ok(HashMap::new());
+        }
+        let contents = std::fs::read_to_string(&self.path)?;
+        if contents.trim().is_empty() {
+            return Ok(HashMap::new());
+        }
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// 先把完整内容写到同目录下的临时文件，再 `rename` 到目标路径；`rename` 是原子的，
+    /// 所以崩溃只可能停在"临时文件没写完"（目标文件毫发无损）或者"已经整体替换完成"
+    /// 这两种状态之一，不会让 `path` 停留在一半新一半旧的状态
+    fn write_entries(&self, entries: &HashMap<String, serde_json::Value>) -> Result<()> {
+        let contents = serde_json::to_string_pretty(entries)?;
+        let tmp_path = self.tmp_path();
+        std::fs::write(&tmp_path, contents)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    fn tmp_path(&self) -> PathBuf {
+        let mut name: OsString = self.path.as_os_str().to_owned();
+        name.push(".tmp");
+        PathBuf::from(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "polyfill_rs_persistent_store_{}_{:?}",
+            name,
+            std::thread::current().id()
+        ))
+    }
+
+    #[test]
+    fn save_and_load_roundtrip_a_value() {
+        let path = temp_path("roundtrip");
+        std::fs::remove_file(&path).ok();
+        let store = PersistentStore::new(&path);
+
+        store.save("count", &42u64).unwrap();
+        assert_eq!(store.load::<u64>("count").unwrap(), Some(42));
+        assert_eq!(store.load::<u64>("missing").unwrap(), None);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn saving_a_new_key_does_not_clobber_an_existing_one() {
+        let path = temp_path("no_clobber");
+        std::fs::remove_file(&path).ok();
+        let store = PersistentStore::new(&path);
+
+        store.save("a", &1u64).unwrap();
+        store.save("b", &2u64).unwrap();
+
+        assert_eq!(store.load::<u64>("a").unwrap(), Some(1));
+        assert_eq!(store.load::<u64>("b").unwrap(), Some(2));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn save_batch_writes_all_keys_in_one_shot() {
+        let path = temp_path("batch");
+        std::fs::remove_file(&path).ok();
+        let store = PersistentStore::new(&path);
+
+        store
+            .save_batch(vec![
+                ("a", serde_json::to_value(1u64).unwrap()),
+                ("b", serde_json::to_value("two").unwrap()),
+            ])
+            .unwrap();
+
+        assert_eq!(store.load::<u64>("a").unwrap(), Some(1));
+        assert_eq!(store.load::<String>("b").unwrap(), Some("two".to_string()));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn no_tmp_file_is_left_behind_after_a_successful_save() {
+        let path = temp_path("tmp_cleanup");
+        std::fs::remove_file(&path).ok();
+        let store = PersistentStore::new(&path);
+
+        store.save("a", &1u64).unwrap();
+
+        assert!(!store.tmp_path().exists());
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,225 @@
+//! 策略框架：`Strategy` 回调 + `StrategyContext` 副作用接口 + 运行时可调参数
+//!
+//! `examples/snipe.rs` 最初把 `process_update` 的分发逻辑和策略本身的状态硬编码在一起，
+//! 每写一个新策略（grid、maker、arb……）都要重新实现一遍"收到什么消息该调用哪个方法"。
+//! 这里把两者拆开：
+//!
+//! - [`Strategy`] 只负责回答"发生了这件事，我想做什么"，通过 `on_book_update` /
+//!   `on_trade` / `on_heartbeat` / `on_timer` 四个回调表达。
+//! - [`StrategyContext`] 负责真正有副作用的操作（下单、撤单、订阅、查仓位、定时器），
+//!   策略通过它与外部世界交互，而不必关心外部世界是真实交易所、回测还是纸上交易。
+//!
+//! [`StrategyParamManager`] 则让 `max_spread_pct`、`min_order_size`、`stale_threshold`
+//! 这类参数可以放在一份 JSON 配置里，运行中随时修改并落盘，不需要重新编译策略。
+
+use crate::errors::Result;
+use crate::fill::FillResult;
+use crate::types::{FillEvent, MarketOrderRequest, OrderDelta, StreamMessage};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use std::time::Duration;
+
+/// 策略需要实现的回调集合。每个回调拿到的 `ctx` 用于发起下单、撤单等有副作用的操作。
+pub trait Strategy {
+    /// 订单簿发生增量更新时触发
+    fn on_book_update(&mut self, ctx: &mut dyn StrategyContext, delta: OrderDelta) -> Result<()>;
+
+    /// 市场上发生了一笔成交（不一定是自己的订单）时触发
+    fn on_trade(&mut self, ctx: &mut dyn StrategyContext, fill: FillEvent) -> Result<()>;
+
+    /// 收到心跳时触发，通常用来检测行情是否过时
+    fn on_heartbeat(&mut self, ctx: &mut dyn StrategyContext, timestamp: u64) -> Result<()>;
+
+    /// 通过 `StrategyContext::add_timer` 注册的定时器到期时触发
+    fn on_timer(&mut self, ctx: &mut dyn StrategyContext, timer_id: &str) -> Result<()>;
+}
+
+/// 把一条 `StreamMessage` 分发给 `Strategy` 对应的回调。
+///
+/// 这就是原先每个策略都要手写一遍的 `process_update` match，抽出来后新策略不需要
+/// 再重新实现消息分发，只需要实现 [`Strategy`] 的四个回调。
+pub fn dispatch(
+    strategy: &mut dyn Strategy,
+    ctx: &mut dyn StrategyContext,
+    message: StreamMessage,
+) -> Result<()> {
+    match message {
+        StreamMessage::BookUpdate { data } => strategy.on_book_update(ctx, data),
+        StreamMessage::Trade { data } => strategy.on_trade(ctx, data),
+        StreamMessage::Heartbeat { timestamp } => strategy.on_heartbeat(ctx, timestamp),
+        _ => Ok(()),
+    }
+}
+
+/// 策略可以对外发起的副作用操作。具体实现可能是连到真实交易所的客户端，
+/// 也可能是回测/纸上交易里的本地模拟。
+pub trait StrategyContext {
+    /// 提交一个市价单请求，返回模拟或真实的成交结果
+    fn place_order(&mut self, request: MarketOrderRequest) -> Result<FillResult>;
+
+    /// 撤销一个挂单
+    fn cancel_order(&mut self, order_id: &str) -> Result<()>;
+
+    /// 订阅一组代币的市场频道
+    fn subscribe(&mut self, token_ids: Vec<String>) -> Result<()>;
+
+    /// 查询某个代币当前持仓（正数为多头，负数为空头）
+    fn query_position(&self, token_id: &str) -> Decimal;
+
+    /// 注册一个定时器，`after` 之后触发策略的 `on_timer(timer_id)`
+    fn add_timer(&mut self, after: Duration, timer_id: String);
+
+    /// 触发一次行情连接的重连（按退避策略重试，重连成功后自动恢复订阅）。
+    /// 纸上交易/回测的 `StrategyContext` 没有真实连接可言，通常是空操作。
+    fn reconnect(&mut self) -> Result<()>;
+}
+
+/// `max_spread_pct`、`min_order_size` 这类可以在运行中调整的参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyParams {
+    pub max_spread_pct: Decimal,
+    pub min_order_size: Decimal,
+    pub max_order_size: Decimal,
+    pub stale_threshold: u64,
+}
+
+/// 从 JSON 配置文件加载参数，支持运行时修改并写回磁盘，这样操作人员可以不重新编译
+/// 就调整一个正在跑的 bot
+#[derive(Debug)]
+pub struct StrategyParamManager {
+    path: PathBuf,
+    params: StrategyParams,
+    last_modified: Option<SystemTime>,
+}
+
+impl StrategyParamManager {
+    /// 从磁盘加载参数文件
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let contents = std::fs::read_to_string(&path)?;
+        let params: StrategyParams = serde_json::from_str(&contents)?;
+        let last_modified = Self::mtime(&path);
+        Ok(Self {
+            path,
+            params,
+            last_modified,
+        })
+    }
+
+    /// 若文件不存在则以给定的默认参数创建它
+    pub fn load_or_create(path: impl AsRef<Path>, defaults: StrategyParams) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if path.exists() {
+            Self::load(path)
+        } else {
+            let mut manager = Self {
+                path,
+                params: defaults,
+                last_modified: None,
+            };
+            manager.persist()?;
+            Ok(manager)
+        }
+    }
+
+    pub fn params(&self) -> &StrategyParams {
+        &self.params
+    }
+
+    /// 应用一次编辑并立即持久化到磁盘
+    pub fn update(&mut self, edit: impl FnOnce(&mut StrategyParams)) -> Result<()> {
+        edit(&mut self.params);
+        self.persist()
+    }
+
+    /// 如果参数文件自上次加载/写入后被改过（比如操作人员手动编辑了它），重新读取一次。
+    ///
+    /// 返回是否真的发生了重新加载，调用方通常在每次心跳时调一下这个方法，这样手改
+    /// 配置文件不需要重启进程就能生效，也不会被下一次 `update()` 悄悄写回覆盖。
+    pub fn reload_if_changed(&mut self) -> Result<bool> {
+        let current_mtime = Self::mtime(&self.path);
+        if current_mtime.is_none() || current_mtime == self.last_modified {
+            return Ok(false);
+        }
+
+        let contents = std::fs::read_to_string(&self.path)?;
+        self.params = serde_json::from_str(&contents)?;
+        self.last_modified = current_mtime;
+        Ok(true)
+    }
+
+    fn mtime(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    fn persist(&mut self) -> Result<()> {
+        let contents = serde_json::to_string_pretty(&self.params)?;
+        std::fs::write(&self.path, contents)?;
+        self.last_modified = Self::mtime(&self.path);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use std::time::Duration;
+
+    fn defaults() -> StrategyParams {
+        StrategyParams {
+            max_spread_pct: dec!(2.0),
+            min_order_size: dec!(10),
+            max_order_size: dec!(100),
+            stale_threshold: 5,
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "polyfill_rs_strategy_param_manager_{}_{:?}",
+            name,
+            std::thread::current().id()
+        ))
+    }
+
+    #[test]
+    fn reload_if_changed_picks_up_a_hand_edited_file() {
+        let path = temp_path("reload");
+        let mut manager = StrategyParamManager::load_or_create(&path, defaults()).unwrap();
+
+        // 还没有人动过文件，不应该重新加载
+        assert!(!manager.reload_if_changed().unwrap());
+
+        // 模拟操作人员手动编辑了参数文件；sleep 一下确保 mtime 真的往前走
+        std::thread::sleep(Duration::from_millis(10));
+        let mut edited = defaults();
+        edited.max_order_size = dec!(250);
+        std::fs::write(&path, serde_json::to_string_pretty(&edited).unwrap()).unwrap();
+
+        assert!(manager.reload_if_changed().unwrap());
+        assert_eq!(manager.params().max_order_size, dec!(250));
+
+        // 重新加载之后再调一次，文件没有变化就不该再次触发
+        assert!(!manager.reload_if_changed().unwrap());
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn update_persists_and_does_not_trigger_a_spurious_reload() {
+        let path = temp_path("update");
+        let mut manager = StrategyParamManager::load_or_create(&path, defaults()).unwrap();
+
+        manager.update(|p| p.max_order_size /= dec!(2)).unwrap();
+        assert_eq!(manager.params().max_order_size, dec!(50));
+
+        // `update()` 自己写的文件，不应该被当成"外部改过"又读一遍
+        assert!(!manager.reload_if_changed().unwrap());
+        assert_eq!(manager.params().max_order_size, dec!(50));
+
+        std::fs::remove_file(&path).ok();
+    }
+}